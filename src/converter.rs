@@ -6,9 +6,15 @@
 //! //
 //! ```
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::io::{BufReader, BufRead};
 use std::fs::File;
+use std::process::Command;
+use std::str::FromStr;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::mem;
+use std::rc::Rc;
 
 /// A macro to determine the file name in a string representing an absolute path.
 /// 
@@ -41,6 +47,431 @@ pub struct Vertex {
 	pub z: f64
 }
 
+/// A structure to manipulate one texture coordinate composed of its `u`, `v` and `w` components,
+/// `w` being the depth of 3D textures (0 for the usual 2D textures).
+#[derive(PartialEq, Copy, Clone)]
+pub struct Uv {
+	pub u: f64,
+	pub v: f64,
+	pub w: f64
+}
+
+/// A structure that represent one face of the model.
+/// Indices are zero-based and refer to the `vertices`, `uvs` and `normals`
+/// arrays of the [Model](./struct.Model.html).
+/// `group` and `object` are the names given by the last `g` and `o` statements. An element
+/// of several groups (`g name1 name2`) goes to the first one, the other names being ignored.
+/// `display` holds the display attributes set by the last `lod`, `bevel`, `c_interp` and `d_interp` statements.
+/// Lines (`l` statement, a polyline through its vertices) and points (`p` statement)
+/// use the same structure, without normals.
+#[derive(PartialEq, Clone, Default)]
+pub struct Face {
+	pub vertex: 	Vec<usize>,
+	pub uv: 		Vec<usize>,
+	pub normal: 	Vec<usize>,
+	pub material: 	usize,
+	pub group: 		String,
+	pub object: 	String,
+	pub display: 	Display
+}
+
+/// A structure that represent the display and render attributes of the elements.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Default, Debug)]
+pub struct Display {
+	/// Level of detail, from 1 to 100, 0 when it is off (`lod`)
+	pub lod: 					u32,
+	/// Bevel interpolation (`bevel on`)
+	pub bevel: 					bool,
+	/// Color interpolation (`c_interp on`)
+	pub color_interpolation: 	bool,
+	/// Dissolve interpolation (`d_interp on`)
+	pub dissolve_interpolation: bool
+}
+
+/// A structure that represent the pivot of one part of the model,
+/// i.e. the position of the part once its vertices have been aligned
+/// around its own bounding box (see [align_parts](./fn.align_parts.html)).
+/// `part` tells whether the part is an object or a group, both of them being named independently.
+#[derive(PartialEq, Clone)]
+pub struct Pivot {
+	pub name: 		String,
+	pub part: 		PartOption,
+	pub position: 	Vertex
+}
+
+/// A structure that represent the content of an OBJ file.
+/// `materials` holds the material names in the order they are first used,
+/// so the position of a name is the material index used by the faces.
+/// `maps` holds the texture map (`usemap`) used with each of them: a material used with
+/// several texture maps appears once per map.
+/// `colors` holds the (r, g, b, a) color of each vertex, it is empty when
+/// no vertex has a color (vertices without one are white).
+/// `parameter_vertices` holds the `vp` statements, as (u, v, w) coordinates.
+/// `shadow_obj` and `trace_obj` are the files of the objects used for shadows and ray tracing (empty when not set).
+#[derive(PartialEq, Clone, Default)]
+pub struct Model {
+	pub faces: 		Vec<Face>,
+	pub lines: 		Vec<Face>,
+	pub points: 	Vec<Face>,
+	pub vertices: 	Vec<Vertex>,
+	pub colors: 	Vec<[f64; 4]>,
+	pub uvs: 		Vec<Uv>,
+	pub normals: 	Vec<Vertex>,
+	pub parameter_vertices: Vec<Uv>,
+	pub materials: 	Vec<String>,
+	pub maps: 		Vec<Option<String>>,
+	pub mtllib: 	String,
+	pub maplibs: 	Vec<String>,
+	pub shadow_obj: String,
+	pub trace_obj: 	String,
+	pub pivots: 	Vec<Pivot>,
+	pub curves: 	Vec<FreeForm>,
+	pub surfaces: 	Vec<FreeForm>
+}
+
+/// The basis of a free-form curve or surface (`cstype` statement).
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Basis {
+	Bezier,
+	BSpline,
+	Cardinal,
+	Taylor,
+	BMatrix
+}
+
+/// The approximation technique of a free-form curve (`ctech`) or surface (`stech`).
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Technique {
+	/// Constant parametric subdivision: number of segments per span in u and v (`cparm`, `cparma`, `cparmb`)
+	Parametric(f64, f64),
+	/// Constant spatial subdivision: maximum length of a segment (`cspace`)
+	Space(f64),
+	/// Curvature dependent subdivision: maximum distance to the curve and maximum angle in degrees (`curv`)
+	Curvature(f64, f64)
+}
+
+/// A structure that represent one free-form curve (`curv`) or surface (`surf`) of an OBJ file,
+/// with the state (`cstype`, `deg`, `ctech` / `stech`) it was defined with.
+/// `control` holds the control vertices (u varying first for surfaces), `weights` their
+/// rational weight (the fourth component of the vertex), and `parameters` the `parm u` and
+/// `parm v` values: the knot vector of a B-spline, the breakpoints of the other bases.
+#[derive(PartialEq, Clone, Debug)]
+pub struct FreeForm {
+	pub basis: 		Basis,
+	pub rational: 	bool,
+	pub degree: 	[usize; 2],
+	pub range: 		[f64; 4],
+	pub control: 	Vec<usize>,
+	pub uvs: 		Vec<usize>,
+	pub weights: 	Vec<f64>,
+	pub parameters: [Vec<f64>; 2],
+	pub technique: 	Option<Technique>,
+	pub trimmed: 	bool,
+	pub material: 	usize,
+	pub group: 		String,
+	pub object: 	String,
+	pub display: 	Display
+}
+
+/// A structure that represent one material of a MTL file.
+/// Fields are named after the Three.js material properties they are exported to,
+/// and are `None` when the MTL file doesn't define them.
+#[derive(PartialEq, Clone, Default)]
+pub struct Material {
+	pub name: 				String,
+	pub color_ambient: 		Option<[f64; 3]>,
+	pub color_diffuse: 		Option<[f64; 3]>,
+	pub color_specular: 	Option<[f64; 3]>,
+	pub specular_coef: 		Option<f64>,
+	pub transparency: 		Option<f64>,
+	pub optical_density: 	Option<f64>,
+	pub illumination: 		Option<i32>,
+	pub map_ambient: 		Option<String>,
+	pub map_diffuse: 		Option<String>,
+	pub map_specular: 		Option<String>,
+	pub map_bump: 			Option<String>,
+	pub map_alpha: 			Option<String>,
+	// PBR extension
+	pub roughness: 				Option<f64>,
+	pub metalness: 				Option<f64>,
+	pub sheen: 					Option<f64>,
+	pub clearcoat: 				Option<f64>,
+	pub clearcoat_roughness: 	Option<f64>,
+	pub anisotropy: 			Option<f64>,
+	pub anisotropy_rotation: 	Option<f64>,
+	pub map_normal: 			Option<String>,
+	pub map_roughness: 			Option<String>,
+	pub map_metalness: 			Option<String>,
+	// Tr, the transparency (1 - d) some exporters write instead of the dissolve
+	pub transparency_inverse: 	Option<f64>,
+	// Derived from the illumination model
+	pub reflectivity: 			Option<f64>,
+	pub transmission: 			Option<f64>
+}
+
+/// The Three.js material models a MTL material can be exported to.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum MaterialModel {
+	/// `MeshBasicMaterial`, for the constant color illumination model (`illum 0`)
+	Basic,
+	/// `MeshLambertMaterial`, for the diffuse illumination model (`illum 1`)
+	Lambert,
+	/// `MeshPhongMaterial`, for the classic MTL statements
+	Phong,
+	/// `MeshStandardMaterial`, for roughness and metalness
+	Standard,
+	/// `MeshPhysicalMaterial`, for sheen, clearcoat, anisotropy and transmission
+	Physical,
+	/// `ShadowMaterial`, for the surfaces only showing shadows (`illum 10`)
+	Shadow
+}
+
+impl FromStr for MaterialModel {
+	type Err = ();
+	fn from_str(src: &str) -> Result<MaterialModel, ()> {
+		match src {
+			"phong" => Ok(MaterialModel::Phong),
+			"standard" => Ok(MaterialModel::Standard),
+			"physical" => Ok(MaterialModel::Physical),
+			_ => Err(())
+		}
+	}
+}
+
+/// This function return the simplest material model able to render
+/// the PBR statements of the given material (`Phong` when it has none).
+///
+/// # Examples
+///
+/// ```
+/// let material = Material { roughness: Some(0.5), ..Material::default() };
+///
+/// assert!(pbr_model(&material) == MaterialModel::Standard);
+/// assert!(pbr_model(&Material::default()) == MaterialModel::Phong);
+/// ```
+pub fn pbr_model(material: &Material) -> MaterialModel {
+
+	let physical = [material.sheen, material.clearcoat, material.clearcoat_roughness, material.anisotropy, material.anisotropy_rotation, material.transmission];
+	
+	if physical.iter().any(Option::is_some) {
+		MaterialModel::Physical
+	} else if material.roughness.is_some() || material.metalness.is_some() || material.map_roughness.is_some() || material.map_metalness.is_some() {
+		MaterialModel::Standard
+	} else {
+		MaterialModel::Phong
+	}
+	
+}
+
+/// This function return the material model rendering the given material like
+/// the source renderer: the PBR statements select their model (see [pbr_model](./fn.pbr_model.html)),
+/// and the illumination model selects it otherwise:
+///
+/// - 0 (constant color): `Basic`
+/// - 1 (diffuse): `Lambert`
+/// - 2 (highlights), 3, 5 and 8 (reflections): `Phong`
+/// - 4 and 9 (glass), 6 and 7 (refraction): `Physical`, using transmission
+/// - 10 (shadows cast onto invisible surfaces): `Shadow`
+///
+/// # Examples
+///
+/// ```
+/// let material = Material { illumination: Some(0), ..Material::default() };
+///
+/// assert!(material_model(&material) == MaterialModel::Basic);
+/// assert!(material_model(&Material::default()) == MaterialModel::Phong);
+/// ```
+pub fn material_model(material: &Material) -> MaterialModel {
+
+	match pbr_model(material) {
+		MaterialModel::Phong => match material.illumination {
+			Some(0) => MaterialModel::Basic,
+			Some(1) => MaterialModel::Lambert,
+			Some(4) | Some(6) | Some(7) | Some(9) => MaterialModel::Physical,
+			Some(10) => MaterialModel::Shadow,
+			_ => MaterialModel::Phong
+		},
+		model => model
+	}
+	
+}
+
+/// This function convert a material to the given model, deriving the values of that model
+/// the MTL file doesn't define from the other ones (the values it defines are kept):
+///
+/// - for the standard and physical models, the roughness is `1 - sqrt(Ns / 900)` (the convention of
+///   Blender, which writes `Ns = 900 (1 - roughness)²`), and the metalness is the strongest component
+///   of Ks for the reflective illumination models (`illum` 3, 5 and 8), 1 when Ks is tinted (its components
+///   differ by more than 0.1, as the highlights of metals take their color) and 0 otherwise.
+///   The base color is Kd mixed with Ks by the metalness, white when there is only `map_Kd`
+///   (so the texture isn't tinted by the debug color).
+///   The physical model uses transmission for the transparent illumination models (`illum` 4, 6, 7 and 9),
+///   `1 - d` when the material is partly dissolved and 1 otherwise.
+/// - for the other models, Ns is derived back from the roughness and the PBR values are dropped;
+///   the reflective illumination models (`illum` 3, 5 and 8) set the reflectivity to the strongest
+///   component of Ks (1 without Ks).
+///
+/// # Examples
+///
+/// ```
+/// let material = Material { specular_coef: Some(225.0), color_specular: Some([0.5, 0.5, 0.5]), ..Material::default() };
+/// let standard = convert_material(&material, MaterialModel::Standard);
+///
+/// assert!(standard.roughness == Some(0.5));
+/// assert!(standard.metalness == Some(0.0));
+/// ```
+pub fn convert_material(material: &Material, model: MaterialModel) -> Material {
+
+	let mut converted = material.clone();
+	
+	let specular = material.color_specular.unwrap_or([0.0; 3]);
+	let strongest = specular.iter().cloned().fold(0.0, f64::max);
+	let weakest = specular.iter().cloned().fold(1.0, f64::min);
+	let reflective = matches!(material.illumination, Some(3) | Some(5) | Some(8));
+	
+	if model != MaterialModel::Standard && model != MaterialModel::Physical {
+		if reflective {
+			converted.reflectivity = Some(if material.color_specular.is_some() { strongest } else { 1.0 });
+		}
+		
+		if let (None, Some(roughness)) = (material.specular_coef, material.roughness) {
+			converted.specular_coef = Some(900.0 * (1.0 - roughness.clamp(0.0, 1.0)).powi(2));
+		}
+		
+		converted.roughness = None;
+		converted.metalness = None;
+		converted.sheen = None;
+		converted.clearcoat = None;
+		converted.clearcoat_roughness = None;
+		converted.anisotropy = None;
+		converted.anisotropy_rotation = None;
+		converted.map_roughness = None;
+		converted.map_metalness = None;
+		converted.transmission = None;
+		
+		return converted;
+	}
+	
+	if let (None, Some(shininess)) = (material.roughness, material.specular_coef) {
+		converted.roughness = Some(1.0 - (shininess.clamp(0.0, 900.0) / 900.0).sqrt());
+	}
+	
+	let metalness = match material.metalness {
+		Some(metalness) => metalness,
+		None if reflective => strongest,
+		None if strongest - weakest > 0.1 => 1.0,
+		None => 0.0
+	};
+	
+	converted.metalness = Some(metalness);
+	
+	if model == MaterialModel::Physical && matches!(material.illumination, Some(4) | Some(6) | Some(7) | Some(9)) {
+		converted.transmission = Some(match material.transparency {
+			Some(dissolve) if dissolve < 1.0 => 1.0 - dissolve,
+			_ => 1.0
+		});
+	}
+	
+	let diffuse = match material.map_diffuse {
+		Some(_) => Some(material.color_diffuse.unwrap_or([1.0; 3])),
+		None => material.color_diffuse
+	};
+	
+	converted.color_diffuse = match (diffuse, material.color_specular) {
+		(Some(diffuse), Some(specular)) if metalness > 0.0 && material.metalness.is_none() => Some([
+			diffuse[0] + (specular[0] - diffuse[0]) * metalness,
+			diffuse[1] + (specular[1] - diffuse[1]) * metalness,
+			diffuse[2] + (specular[2] - diffuse[2]) * metalness
+		]),
+		(None, Some(specular)) if metalness > 0.0 && material.metalness.is_none() => Some(specular),
+		(diffuse, _) => diffuse
+	};
+	
+	converted
+	
+}
+
+/// How the transparency of a material is rendered.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum AlphaMode {
+	/// Fully opaque
+	Opaque,
+	/// Cut out by an alpha texture (foliage, fences), the surface staying opaque and writing its depth
+	Mask,
+	/// Blended with what is behind (glass), without writing its depth
+	Blend
+}
+
+/// This function resolve the opacity of the materials from their `d` and `Tr` statements:
+/// `d` is the opacity (dissolve), or the transparency when `invert` is set (files writing `d 0`
+/// for opaque materials), and `Tr` is the transparency, only used when there is no `d` statement
+/// as exporters usually write both. The opacity is clamped to [0, 1].
+///
+/// # Examples
+///
+/// ```
+/// let mut materials = vec![
+///     Material { transparency: Some(0.25), transparency_inverse: Some(0.5), ..Material::default() },
+///     Material { transparency_inverse: Some(0.5), ..Material::default() }
+/// ];
+///
+/// resolve_transparency(&mut materials, true);
+///
+/// assert!(materials[0].transparency == Some(0.75));
+/// assert!(materials[1].transparency == Some(0.5));
+/// ```
+pub fn resolve_transparency(materials: &mut [Material], invert: bool) {
+
+	for material in materials {
+		let opacity = match (material.transparency, material.transparency_inverse) {
+			(Some(dissolve), _) if invert => Some(1.0 - dissolve),
+			(Some(dissolve), _) => Some(dissolve),
+			(None, Some(transparency)) => Some(1.0 - transparency),
+			(None, None) => None
+		};
+		
+		material.transparency = opacity.map(|opacity| opacity.clamp(0.0, 1.0));
+		material.transparency_inverse = None;
+	}
+	
+}
+
+/// This function return how the transparency of the given material is rendered:
+/// a partial opacity is blended, and an alpha texture (`map_d`) on an opaque material
+/// is a cutout, tested against a threshold.
+///
+/// # Examples
+///
+/// ```
+/// let leaf = Material { map_alpha: Some("leaf.png".to_string()), ..Material::default() };
+///
+/// assert!(alpha_mode(&leaf) == AlphaMode::Mask);
+/// assert!(alpha_mode(&Material { transparency: Some(0.5), ..leaf }) == AlphaMode::Blend);
+/// assert!(alpha_mode(&Material::default()) == AlphaMode::Opaque);
+/// ```
+pub fn alpha_mode(material: &Material) -> AlphaMode {
+
+	match (material.transparency, &material.map_alpha) {
+		(Some(opacity), _) if opacity < 1.0 => AlphaMode::Blend,
+		(_, &Some(_)) => AlphaMode::Mask,
+		_ => AlphaMode::Opaque
+	}
+	
+}
+
+/// This function return the alpha texture of the given material, unless it is its diffuse texture
+/// (`map_d` naming the `map_Kd` image, whose alpha channel is then used by Three.js, as its alpha maps
+/// are read from the green channel).
+pub fn alpha_map(material: &Material) -> &Option<String> {
+
+	if material.map_alpha == material.map_diffuse {
+		&None
+	} else {
+		&material.map_alpha
+	}
+	
+}
+
 /// A structure that represent a box, composed of two vertices `min` and `max`.
 /// This structure has been written mainly to reprensent a bounding box
 /// (see [calculate_bounding_box](./fn.calculate_bounding_box.html) for details)
@@ -51,6 +482,24 @@ pub struct Box {
 	pub max: Vertex
 }
 
+/// A structure that represent a sphere, composed of its `center` and its `radius`
+/// (see [calculate_bounding_sphere](./fn.calculate_bounding_sphere.html) for details).
+#[derive(PartialEq, Copy, Clone)]
+pub struct Sphere {
+	pub center: Vertex,
+	pub radius: f64
+}
+
+/// A structure that represent an oriented box, composed of its `center`,
+/// its `axes` (orthonormal and right-handed) and its `half_size` along each axis
+/// (see [calculate_oriented_bounding_box](./fn.calculate_oriented_bounding_box.html) for details).
+#[derive(PartialEq, Copy, Clone)]
+pub struct OrientedBox {
+	pub center: 	Vertex,
+	pub half_size: 	Vertex,
+	pub axes: 		[Vertex; 3]
+}
+
 /// A function to compare floating point numbers using a tolerance value.
 /// This may not be used outside some tests, so we allow dead_code for that one.
 #[allow(dead_code)]
@@ -58,8 +507,39 @@ pub fn fuzzy_cmp(a: f64, b: f64, tolerance: f64) -> bool {
 	a >= b - tolerance && a <= b + tolerance
 }
 
+/// This function tell whether all the components of the given vertex are finite numbers.
+pub fn is_finite(vertex: &Vertex) -> bool {
+	vertex.x.is_finite() && vertex.y.is_finite() && vertex.z.is_finite()
+}
+
+/// This function list the indices of the vertices having a NaN or infinite component,
+/// which are ignored by the bounding volumes calculations.
+///
+/// # Examples
+///
+/// ```
+/// let vertices = [
+///     Vertex { x: 0.0, y: 0.0, z: 0.0 },
+///     Vertex { x: f64::NAN, y: 0.0, z: 0.0 },
+///     Vertex { x: 0.0, y: 0.0, z: f64::INFINITY }
+/// ];
+///
+/// assert!(find_non_finite_vertices(&vertices) == vec![1, 2]);
+/// ```
+pub fn find_non_finite_vertices(vertices: &[Vertex]) -> Vec<usize> {
+
+	vertices.iter().enumerate()
+		.filter(|&(_, vertex)| !is_finite(vertex))
+		.map(|(index, _)| index)
+		.collect()
+	
+}
+
 /// This function calculate the bounding box of the
 /// given vertices array.
+/// Vertices having a NaN or infinite component are skipped
+/// (see [find_non_finite_vertices](./fn.find_non_finite_vertices.html)),
+/// and `None` is returned when there is no finite vertex at all.
 ///
 /// # Examples
 ///
@@ -68,62 +548,326 @@ pub fn fuzzy_cmp(a: f64, b: f64, tolerance: f64) -> bool {
 ///
 /// let empty_vertices = [];
 /// let vertices = [
-/// 	Vertex { x: 0.0,  y: 0.0, z: 0.0 },
-/// 	Vertex { x: -1.1, y: 1.1, z: 1.1 },
-/// 	Vertex { x: 2.2,  y: 0.0, z: 1.1 },
-/// 	Vertex { x: 1.1,  y: 0.0, z: 2.2 }
+///     Vertex { x: 0.0,  y: 0.0, z: 0.0 },
+///     Vertex { x: -1.1, y: 1.1, z: 1.1 },
+///     Vertex { x: 2.2,  y: 0.0, z: 1.1 },
+///     Vertex { x: 1.1,  y: 0.0, z: 2.2 }
 /// ];
 /// 
-/// let empty_bounding_box = Box {
-/// 	min: Vertex { x: 0.0, y: 0.0, z: 0.0 },
-/// 	max: Vertex { x: 0.0, y: 0.0, z: 0.0 }
-/// };
 /// let bounding_box = Box {
-/// 	min: Vertex { x: -1.1, y: 0.0, z: 0.0 },
-/// 	max: Vertex { x: 2.2, y: 1.1, z: 2.2 } 
+///     min: Vertex { x: -1.1, y: 0.0, z: 0.0 },
+///     max: Vertex { x: 2.2, y: 1.1, z: 2.2 } 
 /// };
 /// 
-/// assert!(bounding_box 		== calculate_bounding_box(&vertices)		);
-/// assert!(empty_bounding_box 	== calculate_bounding_box(&empty_vertices)	);
+/// assert!(Some(bounding_box)     == calculate_bounding_box(&vertices)        );
+/// assert!(calculate_bounding_box(&empty_vertices).is_none());
+/// ```
+pub fn calculate_bounding_box(vertices: &[Vertex]) -> Option<Box> {
+	
+	let mut finite_vertices = vertices.iter().filter(|vertex| is_finite(vertex));
+	
+	let first = *finite_vertices.next()?;
+	
+	let mut bounding_box = Box {
+		min: first,
+		max: first
+	};
+	
+	for vertex in finite_vertices {
+	
+		bounding_box.min.x = bounding_box.min.x.min(vertex.x);
+		bounding_box.min.y = bounding_box.min.y.min(vertex.y);
+		bounding_box.min.z = bounding_box.min.z.min(vertex.z);
+		
+		bounding_box.max.x = bounding_box.max.x.max(vertex.x);
+		bounding_box.max.y = bounding_box.max.y.max(vertex.y);
+		bounding_box.max.z = bounding_box.max.z.max(vertex.z);
+		
+	}
+	
+	Some(bounding_box)
+	
+}
+
+/// This function calculate the bounding box of each part of the model,
+/// parts being named after the `o` or `g` statement of their faces
+/// (see [align_parts](./fn.align_parts.html)).
+/// The box of a part is calculated from the vertices referenced by its faces, lines and points,
+/// and is `None` when the part has no finite vertex.
+/// Parts are returned in the order they first appear.
+///
+/// # Examples
+///
+/// ```
+/// let model = read_obj("\
+///     v 0 0 0\nv 2 0 0\nv 2 2 0\nv 4 4 4\n\
+///     g first\nf 1 2 3\n\
+///     g second\nf 2 3 4\n\
+/// ".as_bytes());
+///
+/// let boxes = calculate_part_bounding_boxes(&model, PartOption::Group);
+///
+/// assert!(boxes[1].0 == "second");
+/// assert!(boxes[1].1.unwrap().min == Vertex { x: 2.0, y: 0.0, z: 0.0 });
 /// ```
-pub fn calculate_bounding_box(vertices: &[Vertex]) -> Box {
+pub fn calculate_part_bounding_boxes(model: &Model, by: PartOption) -> Vec<(String, Option<Box>)> {
+
+	let mut parts: Vec<(String, Vec<Vertex>)> = Vec::new();
 	
-	if vertices.len() > 0 {
+	for face in model.faces.iter().chain(&model.lines).chain(&model.points) {
 	
-		let mut bounding_box = Box {
-			min: vertices[0],
-			max: vertices[0]
+		let name = part_name(face, by);
+		
+		let part = match parts.iter().position(|(n, _)| n == name) {
+			Some(part) => part,
+			None => {
+				parts.push((name.clone(), Vec::new()));
+				parts.len() - 1
+			}
 		};
 		
-		for vertex in &vertices[1..] {
+		parts[part].1.extend(face.vertex.iter().filter_map(|&index| model.vertices.get(index)));
 		
-			if vertex.x < bounding_box.min.x {
-				bounding_box.min.x = vertex.x;
-			} else if vertex.x > bounding_box.max.x {
-				bounding_box.max.x = vertex.x;
+	}
+	
+	parts.into_iter()
+		.map(|(name, vertices)| (name, calculate_bounding_box(&vertices)))
+		.collect()
+	
+}
+
+/// Distance between two vertices
+pub fn distance(a: &Vertex, b: &Vertex) -> f64 {
+	((a.x - b.x).powi(2) + (a.y - b.y).powi(2) + (a.z - b.z).powi(2)).sqrt()
+}
+
+/// Dot product of two vectors
+pub fn dot(a: &Vertex, b: &Vertex) -> f64 {
+	a.x * b.x + a.y * b.y + a.z * b.z
+}
+
+/// Cross product of two vectors
+pub fn cross(a: &Vertex, b: &Vertex) -> Vertex {
+	Vertex {
+		x: a.y * b.z - a.z * b.y,
+		y: a.z * b.x - a.x * b.z,
+		z: a.x * b.y - a.y * b.x
+	}
+}
+
+/// This function calculate a bounding sphere of the given vertices array,
+/// using the Ritter algorithm. The sphere is not the smallest one, but is
+/// at most about 5% bigger, which is good enough for culling.
+/// Vertices having a NaN or infinite component are skipped, and
+/// an array without finite vertex gives a sphere of radius 0.0 at the origin.
+///
+/// # Examples
+///
+/// ```
+/// let vertices = [
+///     Vertex { x: -1.0, y: 0.0, z: 0.0 },
+///     Vertex { x:  1.0, y: 0.0, z: 0.0 },
+///     Vertex { x:  0.0, y: 0.5, z: 0.0 }
+/// ];
+///
+/// let sphere = calculate_bounding_sphere(&vertices);
+///
+/// assert!(sphere.center == Vertex { x: 0.0, y: 0.0, z: 0.0 });
+/// assert!(sphere.radius == 1.0);
+/// ```
+pub fn calculate_bounding_sphere(vertices: &[Vertex]) -> Sphere {
+
+	let vertices: Vec<Vertex> = vertices.iter().filter(|vertex| is_finite(vertex)).cloned().collect();
+	
+	if vertices.is_empty() {
+		return Sphere { center: Vertex { x: 0.0, y: 0.0, z: 0.0 }, radius: 0.0 };
+	}
+	
+	let farthest = |from: &Vertex| -> Vertex {
+		let mut farthest = vertices[0];
+		for vertex in &vertices {
+			if distance(from, vertex) > distance(from, &farthest) {
+				farthest = *vertex;
 			}
+		}
+		farthest
+	};
+	
+	let a = farthest(&vertices[0]);
+	let b = farthest(&a);
+	
+	let mut sphere = Sphere {
+		center: Vertex { x: (a.x + b.x) / 2.0, y: (a.y + b.y) / 2.0, z: (a.z + b.z) / 2.0 },
+		radius: distance(&a, &b) / 2.0
+	};
+	
+	for vertex in &vertices {
+		let d = distance(&sphere.center, vertex);
+		
+		if d > sphere.radius {
+			let radius = (sphere.radius + d) / 2.0;
+			let shift = (radius - sphere.radius) / d;
 			
-			if vertex.y < bounding_box.min.y {
-				bounding_box.min.y = vertex.y;
-			} else if vertex.y > bounding_box.max.y {
-				bounding_box.max.y = vertex.y;
+			sphere.center.x += (vertex.x - sphere.center.x) * shift;
+			sphere.center.y += (vertex.y - sphere.center.y) * shift;
+			sphere.center.z += (vertex.z - sphere.center.z) * shift;
+			sphere.radius = radius;
+		}
+	}
+	
+	sphere
+	
+}
+
+/// Eigen decomposition of a symmetric 3x3 matrix, using the Jacobi method.
+/// Returns the eigen values and the eigen vectors (as columns).
+fn eigen_decomposition(matrix: [[f64; 3]; 3]) -> ([f64; 3], [[f64; 3]; 3]) {
+
+	let mut a = matrix;
+	let mut v = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+	
+	for _ in 0..50 {
+	
+		let off_diagonal = a[0][1].abs() + a[0][2].abs() + a[1][2].abs();
+		
+		if off_diagonal < 1e-15 {
+			break;
+		}
+		
+		for &(p, q) in &[(0, 1), (0, 2), (1, 2)] {
+		
+			if a[p][q] == 0.0 {
+				continue;
 			}
 			
-			if vertex.z < bounding_box.min.z {
-				bounding_box.min.z = vertex.z;
-			} else if vertex.z > bounding_box.max.z {
-				bounding_box.max.z = vertex.z;
+			let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+			let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+			let c = 1.0 / (t * t + 1.0).sqrt();
+			let s = t * c;
+			
+			for row in &mut a {
+				let (akp, akq) = (row[p], row[q]);
+				row[p] = c * akp - s * akq;
+				row[q] = s * akp + c * akq;
+			}
+			let (row_p, row_q) = (a[p], a[q]);
+			for k in 0..3 {
+				a[p][k] = c * row_p[k] - s * row_q[k];
+				a[q][k] = s * row_p[k] + c * row_q[k];
+			}
+			for row in &mut v {
+				let (vkp, vkq) = (row[p], row[q]);
+				row[p] = c * vkp - s * vkq;
+				row[q] = s * vkp + c * vkq;
 			}
 			
 		}
 		
-		return bounding_box;
 	}
 	
-	return Box {
-		min: Vertex { x: 0.0, y: 0.0, z: 0.0 },
-		max: Vertex { x: 0.0, y: 0.0, z: 0.0 }
+	([a[0][0], a[1][1], a[2][2]], v)
+	
+}
+
+/// This function calculate an oriented bounding box of the given vertices array,
+/// using a principal component analysis: the axes of the box are the
+/// eigen vectors of the covariance matrix of the vertices, sorted from the
+/// axis of largest variance to the one of smallest variance.
+/// Vertices having a NaN or infinite component are skipped, and an array without
+/// finite vertex gives an empty box at the origin, aligned on the x, y and z axes.
+///
+/// # Examples
+///
+/// ```
+/// let vertices = [
+///     Vertex { x: 0.0, y: 0.0, z: 0.0 },
+///     Vertex { x: 4.0, y: 4.0, z: 0.0 },
+///     Vertex { x: 1.0, y: -1.0, z: 0.0 },
+///     Vertex { x: 5.0, y: 3.0, z: 0.0 }
+/// ];
+///
+/// let oriented_box = calculate_oriented_bounding_box(&vertices);
+///
+/// assert!(fuzzy_cmp(oriented_box.center.x, 2.5, 0.000001));
+/// assert!(fuzzy_cmp(oriented_box.half_size.x, 2.0 * 2.0f64.sqrt(), 0.000001));
+/// assert!(fuzzy_cmp(oriented_box.half_size.y, 0.5 * 2.0f64.sqrt(), 0.000001));
+/// ```
+pub fn calculate_oriented_bounding_box(vertices: &[Vertex]) -> OrientedBox {
+
+	let mut oriented_box = OrientedBox {
+		center: 	Vertex { x: 0.0, y: 0.0, z: 0.0 },
+		half_size: 	Vertex { x: 0.0, y: 0.0, z: 0.0 },
+		axes: 		[
+			Vertex { x: 1.0, y: 0.0, z: 0.0 },
+			Vertex { x: 0.0, y: 1.0, z: 0.0 },
+			Vertex { x: 0.0, y: 0.0, z: 1.0 }
+		]
+	};
+	
+	let vertices: Vec<Vertex> = vertices.iter().filter(|vertex| is_finite(vertex)).cloned().collect();
+	
+	if vertices.is_empty() {
+		return oriented_box;
+	}
+	
+	let count = vertices.len() as f64;
+	let mean = Vertex {
+		x: vertices.iter().map(|vertex| vertex.x).sum::<f64>() / count,
+		y: vertices.iter().map(|vertex| vertex.y).sum::<f64>() / count,
+		z: vertices.iter().map(|vertex| vertex.z).sum::<f64>() / count
+	};
+	
+	let mut covariance = [[0.0; 3]; 3];
+	
+	for vertex in &vertices {
+		let d = [vertex.x - mean.x, vertex.y - mean.y, vertex.z - mean.z];
+		for i in 0..3 {
+			for j in 0..3 {
+				covariance[i][j] += d[i] * d[j] / count;
+			}
+		}
+	}
+	
+	let (values, vectors) = eigen_decomposition(covariance);
+	
+	let mut order = [0, 1, 2];
+	order.sort_by(|&a, &b| values[b].partial_cmp(&values[a]).unwrap_or(::std::cmp::Ordering::Equal));
+	
+	for (axis, &column) in oriented_box.axes.iter_mut().zip(&order) {
+		*axis = Vertex { x: vectors[0][column], y: vectors[1][column], z: vectors[2][column] };
+		normalize(axis);
+	}
+	
+	// enforce a right-handed basis, so the axes form a rotation
+	oriented_box.axes[2] = cross(&oriented_box.axes[0], &oriented_box.axes[1]);
+	normalize(&mut oriented_box.axes[2]);
+	
+	let mut min = [f64::INFINITY; 3];
+	let mut max = [f64::NEG_INFINITY; 3];
+	
+	for vertex in &vertices {
+		for i in 0..3 {
+			let projection = dot(vertex, &oriented_box.axes[i]);
+			min[i] = min[i].min(projection);
+			max[i] = max[i].max(projection);
+		}
+	}
+	
+	let middle = [(min[0] + max[0]) / 2.0, (min[1] + max[1]) / 2.0, (min[2] + max[2]) / 2.0];
+	let axes = oriented_box.axes;
+	
+	oriented_box.center = Vertex {
+		x: axes[0].x * middle[0] + axes[1].x * middle[1] + axes[2].x * middle[2],
+		y: axes[0].y * middle[0] + axes[1].y * middle[1] + axes[2].y * middle[2],
+		z: axes[0].z * middle[0] + axes[1].z * middle[1] + axes[2].z * middle[2]
 	};
+	oriented_box.half_size = Vertex {
+		x: (max[0] - min[0]) / 2.0,
+		y: (max[1] - min[1]) / 2.0,
+		z: (max[2] - min[2]) / 2.0
+	};
+	
+	oriented_box
 	
 }
 
@@ -135,17 +879,17 @@ pub fn calculate_bounding_box(vertices: &[Vertex]) -> Box {
 /// use converter::{fuzzy_cmp, translate};
 /// 
 /// let mut vertices = [
-/// 	Vertex { x: 0.0,  y: 0.0, z: 0.0 },
-/// 	Vertex { x: -1.1, y: 1.1, z: 1.1 },
-/// 	Vertex { x: 2.2,  y: 0.0, z: 1.1 },
-/// 	Vertex { x: 1.1,  y: 0.0, z: 2.2 }
+///     Vertex { x: 0.0,  y: 0.0, z: 0.0 },
+///     Vertex { x: -1.1, y: 1.1, z: 1.1 },
+///     Vertex { x: 2.2,  y: 0.0, z: 1.1 },
+///     Vertex { x: 1.1,  y: 0.0, z: 2.2 }
 /// ];
 /// 
 /// let translated_vertices = [
-/// 	Vertex { x: 1.0,  y: -2.0, z: 3.3 },
-/// 	Vertex { x: -0.1, y: -0.9, z: 4.4 },
-/// 	Vertex { x: 3.2,  y: -2.0, z: 4.4 },
-/// 	Vertex { x: 2.1,  y: -2.0, z: 5.5 }
+///     Vertex { x: 1.0,  y: -2.0, z: 3.3 },
+///     Vertex { x: -0.1, y: -0.9, z: 4.4 },
+///     Vertex { x: 3.2,  y: -2.0, z: 4.4 },
+///     Vertex { x: 2.1,  y: -2.0, z: 5.5 }
 /// ];
 /// 
 /// let translation_vector = [1.0, -2.0, 3.3];
@@ -153,12 +897,12 @@ pub fn calculate_bounding_box(vertices: &[Vertex]) -> Box {
 /// translate(&mut vertices, &translation_vector);
 /// 
 /// for it in vertices.iter_mut().zip(translated_vertices.iter()) {
-/// 	let (vertex, translated_vertex) = it;
-/// 	assert!(
-/// 		fuzzy_cmp(vertex.x, translated_vertex.x, 0.01) &&
-/// 		fuzzy_cmp(vertex.y, translated_vertex.y, 0.01) &&
-/// 		fuzzy_cmp(vertex.z, translated_vertex.z, 0.01)
-/// 	);
+///     let (vertex, translated_vertex) = it;
+///     assert!(
+///         fuzzy_cmp(vertex.x, translated_vertex.x, 0.01) &&
+///         fuzzy_cmp(vertex.y, translated_vertex.y, 0.01) &&
+///         fuzzy_cmp(vertex.z, translated_vertex.z, 0.01)
+///     );
 /// }
 /// ```
 pub fn translate(vertices: &mut [Vertex], translation_vector: &[f64; 3]) {
@@ -172,6 +916,7 @@ pub fn translate(vertices: &mut [Vertex], translation_vector: &[f64; 3]) {
 }
 
 /// Alignment option for the align function
+#[allow(non_camel_case_types)]
 pub enum align_option {
 	/// Align the model on the center of the bounding box
 	center,
@@ -189,15 +934,15 @@ pub enum align_option {
 ///
 /// ```
 /// let mut vertices = [
-/// 	Vertex { x: 0.0,  y: 0.0, z: 0.0 },
-/// 	Vertex { x: 2.0,  y: 2.0, z: 2.0 },
-/// 	Vertex { x: 4.0,  y: 4.0, z: 4.0 },
+///     Vertex { x: 0.0,  y: 0.0, z: 0.0 },
+///     Vertex { x: 2.0,  y: 2.0, z: 2.0 },
+///     Vertex { x: 4.0,  y: 4.0, z: 4.0 },
 /// ];
 /// 
 /// let translated_vertices = [
-/// 	Vertex { x: -2.0,  y: -4.0, z: -2.0 },
-/// 	Vertex { x:  0.0,  y: -2.0, z:  0.0 },
-/// 	Vertex { x:  2.0,  y:  0.0, z:  2.0 },
+///     Vertex { x: -2.0,  y: -4.0, z: -2.0 },
+///     Vertex { x:  0.0,  y: -2.0, z:  0.0 },
+///     Vertex { x:  2.0,  y:  0.0, z:  2.0 },
 /// ];
 /// 
 /// align(&mut vertices, align_option::top);
@@ -206,7 +951,10 @@ pub enum align_option {
 /// ```
 pub fn align(vertices: &mut [Vertex], direction: align_option) {
 	
-	let bounding_box = calculate_bounding_box(vertices);
+	let bounding_box = match calculate_bounding_box(vertices) {
+		Some(bounding_box) => bounding_box,
+		None => return
+	};
 	
 	let cx = bounding_box.min.x + (bounding_box.max.x - bounding_box.min.x) / 2.0;
 	let cz = bounding_box.min.z + (bounding_box.max.z - bounding_box.min.z) / 2.0;
@@ -227,15 +975,15 @@ pub fn align(vertices: &mut [Vertex], direction: align_option) {
 ///
 /// ```
 /// let mut vertices = [
-/// 	Vertex { x: 0.0,  y: 0.0, z: 0.0 },
-/// 	Vertex { x: 2.0,  y: 2.0, z: 2.0 },
-/// 	Vertex { x: 4.0,  y: 4.0, z: 4.0 },
+///     Vertex { x: 0.0,  y: 0.0, z: 0.0 },
+///     Vertex { x: 2.0,  y: 2.0, z: 2.0 },
+///     Vertex { x: 4.0,  y: 4.0, z: 4.0 },
 /// ];
 /// 
 /// let translated_vertices = [
-/// 	Vertex { x: -2.0,  y: -2.0, z: -2.0 },
-/// 	Vertex { x:  0.0,  y:  0.0, z:  0.0 },
-/// 	Vertex { x:  2.0,  y:  2.0, z:  2.0 },
+///     Vertex { x: -2.0,  y: -2.0, z: -2.0 },
+///     Vertex { x:  0.0,  y:  0.0, z:  0.0 },
+///     Vertex { x:  2.0,  y:  2.0, z:  2.0 },
 /// ];
 /// 
 /// center(&mut vertices);
@@ -255,15 +1003,15 @@ pub fn center(vertices: &mut [Vertex]) {
 ///
 /// ```
 /// let mut vertices = [
-/// 	Vertex { x: 0.0,  y: 0.0, z: 0.0 },
-/// 	Vertex { x: 2.0,  y: 2.0, z: 2.0 },
-/// 	Vertex { x: 4.0,  y: 4.0, z: 4.0 },
+///     Vertex { x: 0.0,  y: 0.0, z: 0.0 },
+///     Vertex { x: 2.0,  y: 2.0, z: 2.0 },
+///     Vertex { x: 4.0,  y: 4.0, z: 4.0 },
 /// ];
 /// 
 /// let translated_vertices = [
-/// 	Vertex { x: -2.0,  y: -4.0, z: -2.0 },
-/// 	Vertex { x:  0.0,  y: -2.0, z:  0.0 },
-/// 	Vertex { x:  2.0,  y:  0.0, z:  2.0 },
+///     Vertex { x: -2.0,  y: -4.0, z: -2.0 },
+///     Vertex { x:  0.0,  y: -2.0, z:  0.0 },
+///     Vertex { x:  2.0,  y:  0.0, z:  2.0 },
 /// ];
 /// 
 /// align_top(&mut vertices);
@@ -283,15 +1031,15 @@ pub fn align_top(vertices: &mut [Vertex]) {
 ///
 /// ```
 /// let mut vertices = [
-/// 	Vertex { x: 0.0,  y: -2.0, z: 0.0 },
-/// 	Vertex { x: 2.0,  y:  2.0, z: 2.0 },
-/// 	Vertex { x: 4.0,  y:  4.0, z: 4.0 },
+///     Vertex { x: 0.0,  y: -2.0, z: 0.0 },
+///     Vertex { x: 2.0,  y:  2.0, z: 2.0 },
+///     Vertex { x: 4.0,  y:  4.0, z: 4.0 },
 /// ];
 /// 
 /// let translated_vertices = [
-/// 	Vertex { x: -2.0,  y:  0.0, z: -2.0 },
-/// 	Vertex { x:  0.0,  y:  4.0, z:  0.0 },
-/// 	Vertex { x:  2.0,  y:  6.0, z:  2.0 },
+///     Vertex { x: -2.0,  y:  0.0, z: -2.0 },
+///     Vertex { x:  0.0,  y:  4.0, z:  0.0 },
+///     Vertex { x:  2.0,  y:  6.0, z:  2.0 },
 /// ];
 /// 
 /// align_bottom(&mut vertices);
@@ -310,15 +1058,15 @@ pub fn align_bottom(vertices: &mut [Vertex]) {
 ///
 /// ```
 /// let mut vertices = [
-/// 	Vertex { x: 0.0,  y: -2.0, z: 0.0 },
-/// 	Vertex { x: 2.0,  y:  2.0, z: 2.0 },
-/// 	Vertex { x: 4.0,  y:  4.0, z: 4.0 },
+///     Vertex { x: 0.0,  y: -2.0, z: 0.0 },
+///     Vertex { x: 2.0,  y:  2.0, z: 2.0 },
+///     Vertex { x: 4.0,  y:  4.0, z: 4.0 },
 /// ];
 /// 
 /// let translated_vertices = [
-/// 	Vertex { x: -2.0,  y:  -2.0, z: -2.0 },
-/// 	Vertex { x:  0.0,  y:   2.0, z:  0.0 },
-/// 	Vertex { x:  2.0,  y:   4.0, z:  2.0 },
+///     Vertex { x: -2.0,  y:  -2.0, z: -2.0 },
+///     Vertex { x:  0.0,  y:   2.0, z:  0.0 },
+///     Vertex { x:  2.0,  y:   4.0, z:  2.0 },
 /// ];
 /// 
 /// center_xz(&mut vertices);
@@ -331,304 +1079,3707 @@ pub fn center_xz(vertices: &mut [Vertex]) {
 	
 }
 
-/// This function normalize the given vertex
-/// 
+/// This function scale the given vertices by the given factor, around the origin
+///
 /// # Examples
-/// 
+///
 /// ```
-/// use converter::{fuzzy_comp, normalize};
+/// let mut vertices = [
+///     Vertex { x: 0.0,  y: -2.0, z: 0.0 },
+///     Vertex { x: 2.0,  y:  2.0, z: 2.0 },
+/// ];
 /// 
-/// let mut v = Vertex { x: 1.0, y: 1.0, z: 1.0 };
+/// let scaled_vertices = [
+///     Vertex { x: 0.0,  y: -1.0, z: 0.0 },
+///     Vertex { x: 1.0,  y:  1.0, z: 1.0 },
+/// ];
 /// 
-/// let vn = Vertex { x: 0.57735, y: 0.57735, z: 0.57735 };
+/// scale(&mut vertices, 0.5);
 /// 
-/// normalize(&mut v1);
-/// assert!(
-/// 	fuzzy_cmp(v1.x, v1n.x, 0.000001) &&
-/// 	fuzzy_cmp(v1.y, v1n.y, 0.000001) &&
-/// 	fuzzy_cmp(v1.z, v1n.z, 0.000001)
+/// assert!(vertices == scaled_vertices);
+/// ```
+pub fn scale(vertices: &mut [Vertex], factor: f64) {
+
+	for vertex in vertices {
+		vertex.x *= factor;
+		vertex.y *= factor;
+		vertex.z *= factor;
+	}
+	
+}
+
+/// This function uniformly scale the model, around the origin, so the largest dimension
+/// of its bounding box equals the given size. It may be combined with the
+/// alignment functions, which should be called after this one.
+/// Returns the applied factor, which is 1.0 when the model has no size.
+/// 
+/// # Examples
+///
+/// ```
+/// let mut vertices = [
+///     Vertex { x: 0.0,  y: 0.0, z: 0.0 },
+///     Vertex { x: 4.0,  y: 2.0, z: 1.0 },
+/// ];
+/// 
+/// let scaled_vertices = [
+///     Vertex { x: 0.0,  y: 0.0, z: 0.0 },
+///     Vertex { x: 1.0,  y: 0.5, z: 0.25 },
+/// ];
+/// 
+/// assert!(fit(&mut vertices, 1.0) == 0.25);
+/// assert!(vertices == scaled_vertices);
+/// ```
+pub fn fit(vertices: &mut [Vertex], size: f64) -> f64 {
+
+	let bounding_box = match calculate_bounding_box(vertices) {
+		Some(bounding_box) => bounding_box,
+		None => return 1.0
+	};
+	
+	let largest = (bounding_box.max.x - bounding_box.min.x)
+		.max(bounding_box.max.y - bounding_box.min.y)
+		.max(bounding_box.max.z - bounding_box.min.z);
+	
+	if !largest.is_normal() {
+		return 1.0;
+	}
+	
+	let factor = size / largest;
+	
+	scale(vertices, factor);
+	
+	factor
+	
+}
+
+/// This function normalize the model into the unit cube:
+/// the largest dimension of the model becomes 1.0 and the model is centered
+/// on the middle of its bounding box, so it fits in [-0.5, 0.5] on every axis.
+/// 
+/// # Examples
+///
+/// ```
+/// let mut vertices = [
+///     Vertex { x: 0.0,  y: 0.0, z: 0.0 },
+///     Vertex { x: 4.0,  y: 2.0, z: 2.0 },
+/// ];
+/// 
+/// let normalized_vertices = [
+///     Vertex { x: -0.5,  y: -0.25, z: -0.25 },
+///     Vertex { x:  0.5,  y:  0.25, z:  0.25 },
+/// ];
+/// 
+/// fit_unit_cube(&mut vertices);
+/// 
+/// assert!(vertices == normalized_vertices);
+/// ```
+pub fn fit_unit_cube(vertices: &mut [Vertex]) {
+	
+	fit(vertices, 1.0);
+	center(vertices);
+	
+}
+
+/// The OBJ statement used to split the model into parts
+#[derive(PartialEq, Copy, Clone)]
+pub enum PartOption {
+	/// One part per object (`o` statement)
+	Object,
+	/// One part per group (`g` statement)
+	Group
+}
+
+/// Name of the part the face belongs to
+fn part_name(face: &Face, by: PartOption) -> &String {
+	match by {
+		PartOption::Object => &face.object,
+		PartOption::Group => &face.group
+	}
+}
+
+/// A structure that represent a named subset of the faces, lines and points of a model
+/// (see [split_model](./fn.split_model.html)).
+#[derive(PartialEq, Clone, Default)]
+pub struct Mesh {
+	pub name: 	String,
+	pub faces: 	Vec<Face>,
+	pub lines: 	Vec<Face>,
+	pub points: Vec<Face>
+}
+
+/// This function split the faces, lines and points of the model into one mesh per part,
+/// parts being named after the `o` or `g` statement of their elements.
+/// Without part option, all the elements are merged into a single unnamed mesh.
+/// Meshes are returned in the order they first appear (faces first, then lines
+/// and points), and keep sharing the vertices, normals and uvs of the model.
+///
+/// # Examples
+///
+/// ```
+/// let model = read_obj("\
+///     v 0 0 0\nv 2 0 0\nv 2 2 0\n\
+///     o first\nf 1 2 3\n\
+///     o second\nf 3 2 1\n\
+/// ".as_bytes());
+///
+/// assert!(split_model(&model, Some(PartOption::Object)).len() == 2);
+/// assert!(split_model(&model, None).len() == 1);
+/// ```
+pub fn split_model(model: &Model, by: Option<PartOption>) -> Vec<Mesh> {
+
+	let by = match by {
+		Some(by) => by,
+		None => return vec![Mesh {
+			name: 	String::new(),
+			faces: 	model.faces.clone(),
+			lines: 	model.lines.clone(),
+			points: model.points.clone()
+		}]
+	};
+	
+	let mut meshes: Vec<Mesh> = Vec::new();
+	
+	fn mesh<'a>(meshes: &'a mut Vec<Mesh>, name: &str) -> &'a mut Mesh {
+		let index = match meshes.iter().position(|mesh| mesh.name == name) {
+			Some(index) => index,
+			None => {
+				meshes.push(Mesh { name: name.to_string(), ..Mesh::default() });
+				meshes.len() - 1
+			}
+		};
+		&mut meshes[index]
+	}
+	
+	for face in &model.faces {
+		mesh(&mut meshes, part_name(face, by)).faces.push(face.clone());
+	}
+	for line in &model.lines {
+		mesh(&mut meshes, part_name(line, by)).lines.push(line.clone());
+	}
+	for point in &model.points {
+		mesh(&mut meshes, part_name(point, by)).points.push(point.clone());
+	}
+	
+	meshes
+	
+}
+
+/// This function align each part of the model on the center of its own bounding box,
+/// and record the position of that center as the pivot of the part in the model.
+/// Parts are named after the `o` or `g` statement of their faces, lines and points, and
+/// a vertex shared by several parts is duplicated so each part can be moved on its own.
+/// 
+/// # Examples
+///
+/// ```
+/// let mut model = read_obj("\
+///     v 0 0 0\nv 2 0 0\nv 2 2 0\n\
+///     v 4 4 4\nv 6 4 4\nv 6 6 4\n\
+///     o first\nf 1 2 3\n\
+///     o second\nf 4 5 6\n\
+/// ".as_bytes());
+///
+/// align_parts(&mut model, PartOption::Object);
+///
+/// assert!(model.vertices[0] == Vertex { x: -1.0, y: -1.0, z: 0.0 });
+/// assert!(model.pivots[1].name == "second");
+/// assert!(model.pivots[1].position == Vertex { x: 5.0, y: 5.0, z: 4.0 });
+/// ```
+pub fn align_parts(model: &mut Model, by: PartOption) {
+
+	let mut names: 	Vec<String> 						= Vec::new();
+	let mut owners: Vec<Option<usize>> 					= vec![None; model.vertices.len()];
+	let mut copies: HashMap<(usize, usize), usize> 		= HashMap::new();
+	
+	for face in model.faces.iter_mut().chain(model.lines.iter_mut()).chain(model.points.iter_mut()) {
+	
+		let name = part_name(face, by);
+		
+		let part = match names.iter().position(|n| n == name) {
+			Some(part) => part,
+			None => {
+				names.push(name.clone());
+				names.len() - 1
+			}
+		};
+		
+		for index in &mut face.vertex {
+		
+			if *index >= owners.len() {
+				continue;
+			}
+			
+			match owners[*index] {
+				None => owners[*index] = Some(part),
+				Some(owner) if owner == part => (),
+				Some(_) => {
+					let original = *index;
+					*index = match copies.get(&(part, original)) {
+						Some(&copy) => copy,
+						None => {
+							let vertex = model.vertices[original];
+							model.vertices.push(vertex);
+							if !model.colors.is_empty() {
+								let color = model.colors[original];
+								model.colors.push(color);
+							}
+							owners.push(Some(part));
+							copies.insert((part, original), model.vertices.len() - 1);
+							model.vertices.len() - 1
+						}
+					};
+				}
+			}
+			
+		}
+		
+	}
+	
+	// each vertex now belongs to a single part
+	for (part, (name, bounding_box)) in calculate_part_bounding_boxes(model, by).into_iter().enumerate() {
+	
+		let pivot = match bounding_box {
+			Some(bounding_box) => Vertex {
+				x: bounding_box.min.x + (bounding_box.max.x - bounding_box.min.x) / 2.0,
+				y: bounding_box.min.y + (bounding_box.max.y - bounding_box.min.y) / 2.0,
+				z: bounding_box.min.z + (bounding_box.max.z - bounding_box.min.z) / 2.0
+			},
+			None => Vertex { x: 0.0, y: 0.0, z: 0.0 }
+		};
+		
+		for (vertex, &owner) in model.vertices.iter_mut().zip(&owners) {
+			if owner == Some(part) {
+				translate(::std::slice::from_mut(vertex), &[-pivot.x, -pivot.y, -pivot.z]);
+			}
+		}
+		
+		model.pivots.push(Pivot { name, part: by, position: pivot });
+		
+	}
+	
+}
+
+/// This function normalize the given vertex
+/// 
+/// # Examples
+/// 
+/// ```
+/// use converter::{fuzzy_comp, normalize};
+/// 
+/// let mut v = Vertex { x: 1.0, y: 1.0, z: 1.0 };
+/// 
+/// let vn = Vertex { x: 0.57735, y: 0.57735, z: 0.57735 };
+/// 
+/// normalize(&mut v1);
+/// assert!(
+///     fuzzy_cmp(v1.x, v1n.x, 0.000001) &&
+///     fuzzy_cmp(v1.y, v1n.y, 0.000001) &&
+///     fuzzy_cmp(v1.z, v1n.z, 0.000001)
 /// );
 /// ```
 pub fn normalize(vertex: &mut Vertex) {
 	
-	let lenght = (vertex.x.powi(2) + vertex.y.powi(2) + vertex.z.powi(2)).sqrt();
+	let lenght = (vertex.x.powi(2) + vertex.y.powi(2) + vertex.z.powi(2)).sqrt();
+	
+	if lenght.is_normal() {
+		vertex.x /= lenght;
+		vertex.y /= lenght;
+		vertex.z /= lenght;
+	}
+	 
+}
+
+/// This function parse the MTL file with the given name.
+/// See [read_mtl_checked](./fn.read_mtl_checked.html) for details.
+///
+/// # Panics
+///
+/// Panics if the file can't be opened.
+pub fn parse_mtl(file_name: & str, strict: bool) -> Checked<Vec<Material>> {
+
+	let file = match File::open(file_name) {
+		Ok(file) => file,
+		Err(_) => panic!("Couldn't open {}", file_name)
+	};
+
+	let (materials, mut issues) = read_mtl_checked(&read_statements(BufReader::new(&file), file_name), strict);
+	
+	if strict && !issues.is_empty() {
+		return Err(issues.swap_remove(0));
+	}
+	
+	Ok((materials, issues))
+	
+}
+
+/// This function read the materials of a MTL content.
+/// Materials are returned in the order they are defined.
+/// Invalid statements are skipped, see [read_mtl_checked](./fn.read_mtl_checked.html)
+/// (the converter uses [parse_mtl](./fn.parse_mtl.html), so we allow dead_code for this one).
+///
+/// # Examples
+///
+/// ```
+/// let materials = read_mtl("newmtl red\nKd 1.0 0.0 0.0\n".as_bytes());
+///
+/// assert!(materials[0].name == "red");
+/// assert!(materials[0].color_diffuse == Some([1.0, 0.0, 0.0]));
+/// ```
+#[allow(dead_code)]
+pub fn read_mtl<R: BufRead>(reader: R) -> Vec<Material> {
+
+	read_mtl_checked(&read_statements(reader, ""), false).0
+	
+}
+
+/// This function read the statements of a MTL content (see [read_statements](./fn.read_statements.html)),
+/// and return the materials with the issues found (one per invalid statement, which is skipped):
+/// unknown statements, wrong values and statements found before the first `newmtl`.
+/// When `strict` is set, the reading stops at the first issue.
+///
+/// # Examples
+///
+/// ```
+/// let statements = read_statements("newmtl red\nKd 1.0 nan 0.0\n".as_bytes(), "model.mtl");
+/// let (materials, issues) = read_mtl_checked(&statements, false);
+///
+/// assert!(materials[0].color_diffuse.is_none());
+/// assert!(issues[0].to_string() == "model.mtl:2:8: nan isn't a finite number");
+/// ```
+pub fn read_mtl_checked(statements: &[Statement], strict: bool) -> (Vec<Material>, Vec<Issue>) {
+
+	let mut materials: Vec<Material> = Vec::new();
+	let mut issues = Vec::new();
+	
+	for statement in statements {
+	
+		let chunks: Vec<&str> = statement.text.split_whitespace().collect();
+		
+		if chunks.is_empty() {
+			continue;
+		}
+		
+		// New material
+		// newmtl material_name
+		if chunks[0] == "newmtl" {
+			let name = if chunks.len() > 1 { chunks[1] } else { "" };
+			materials.push(Material { name: name.to_string(), ..Material::default() });
+			continue;
+		}
+		
+		let result = match materials.last_mut() {
+			None => Err((0, IssueKind::MisplacedStatement, format!("{} before newmtl", chunks[0]))),
+			Some(material) => match chunks[0] {
+				// Diffuse, ambient and specular colors
+				// Kd 1.000 1.000 1.000
+				"Kd" => parse_color(&chunks).map(|color| material.color_diffuse = Some(color)),
+				"Ka" => parse_color(&chunks).map(|color| material.color_ambient = Some(color)),
+				"Ks" => parse_color(&chunks).map(|color| material.color_specular = Some(color)),
+				// Specular coefficient
+				// Ns 154.000
+				"Ns" => parse_value(&chunks).map(|value| material.specular_coef = Some(value)),
+				// Transparency
+				// d 0.9
+				"d" => parse_value(&chunks).map(|value| material.transparency = Some(value)),
+				// Tr 0.1
+				"Tr" => parse_value(&chunks).map(|value| material.transparency_inverse = Some(value)),
+				// Optical density
+				// Ni 1.0
+				"Ni" => parse_value(&chunks).map(|value| material.optical_density = Some(value)),
+				// PBR extension: roughness, metallic, sheen, clearcoat thickness and roughness,
+				// anisotropy and its rotation (as a fraction of a turn)
+				// Pr 0.5
+				"Pr" => parse_value(&chunks).map(|value| material.roughness = Some(value)),
+				"Pm" => parse_value(&chunks).map(|value| material.metalness = Some(value)),
+				"Ps" => parse_value(&chunks).map(|value| material.sheen = Some(value)),
+				"Pc" => parse_value(&chunks).map(|value| material.clearcoat = Some(value)),
+				"Pcr" => parse_value(&chunks).map(|value| material.clearcoat_roughness = Some(value)),
+				"aniso" => parse_value(&chunks).map(|value| material.anisotropy = Some(value)),
+				"anisor" => parse_value(&chunks).map(|value| material.anisotropy_rotation = Some(value)),
+				// Illumination
+				// illum 2
+				"illum" => count_values(&chunks, 1, 1).and_then(|_| match chunks[1].parse() {
+					Ok(illumination) => {
+						material.illumination = Some(illumination);
+						Ok(())
+					}
+					Err(_) => Err((1, IssueKind::InvalidValue, format!("{} isn't an illumination model", chunks[1])))
+				}),
+				// Textures, the options preceding the file name being ignored, and its path being
+				// kept as is (see texture::find_texture)
+				// map_Kd [-s 1 1 1] texture_diffuse.jpg
+				// norm normal_map.png
+				// map_Pr roughness_map.png
+				keyword @ ("map_Kd" | "map_Ka" | "map_Ks" | "map_Bump" | "map_bump" | "bump" | "map_d" | "norm" | "map_Pr" | "map_Pm") => {
+					count_values(&chunks, 1, usize::MAX).map(|_| {
+						let map = Some(chunks[chunks.len() - 1].to_string());
+						
+						match keyword {
+							"map_Kd" => material.map_diffuse = map,
+							"map_Ka" => material.map_ambient = map,
+							"map_Ks" => material.map_specular = map,
+							"map_d" => material.map_alpha = map,
+							"norm" => material.map_normal = map,
+							"map_Pr" => material.map_roughness = map,
+							"map_Pm" => material.map_metalness = map,
+							_ => material.map_bump = map
+						}
+					})
+				}
+				// Statements without effect on the conversion
+				"Ke" | "Tf" | "sharpness" | "map_Ke" | "map_Ns" | "map_refl" | "refl" | "disp" | "decal" => Ok(()),
+				keyword => Err((0, IssueKind::UnknownStatement, format!("unknown statement {}", keyword)))
+			}
+		};
+		
+		if let Err(problem) = result {
+			issues.push(issue(statement, problem));
+			
+			if strict {
+				break;
+			}
+		}
+		
+	}
+	
+	(materials, issues)
+	
+}
+
+/// Parse the three components of a color statement.
+fn parse_color(chunks: &[&str]) -> Result<[f64; 3], Problem> {
+	
+	count_values(chunks, 3, 3)?;
+	
+	let values = parse_numbers(chunks, 1)?;
+	
+	Ok([values[0], values[1], values[2]])
+	
+}
+
+/// Parse the value of a statement with a single number.
+fn parse_value(chunks: &[&str]) -> Result<f64, Problem> {
+	
+	count_values(chunks, 1, 1)?;
+	
+	Ok(parse_numbers(chunks, 1)?[0])
+	
+}
+
+/// Settings of the OBJ and MTL parsing.
+#[derive(PartialEq, Copy, Clone, Default)]
+pub struct ParseSettings {
+	/// Run the shell commands of the `csh` statements, which are ignored otherwise
+	pub allow_csh: 	bool,
+	/// Stop at the first invalid statement, which is skipped otherwise
+	pub strict: 	bool
+}
+
+/// A statement of an OBJ or MTL content, with the file and the line it starts on
+/// (statements can be continued on several lines), see [read_statements](./fn.read_statements.html).
+#[derive(PartialEq, Clone, Debug)]
+pub struct Statement {
+	pub file: 		Rc<str>,
+	pub line: 		usize,
+	/// The tokens of the statement, separated by single spaces
+	pub text: 		String,
+	/// The (line, column) of each token, followed by the position after the last one
+	pub positions: 	Vec<(usize, usize)>
+}
+
+/// The kinds of invalid statements, the statement (or the element it defines) being skipped.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub enum IssueKind {
+	UnknownStatement,
+	InvalidValue,
+	NonFiniteValue,
+	InvalidIndex,
+	IndexOutOfRange,
+	SkippedReference,
+	MisplacedStatement
+}
+
+impl fmt::Display for IssueKind {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			IssueKind::UnknownStatement => write!(f, "unknown statement"),
+			IssueKind::InvalidValue => write!(f, "invalid value"),
+			IssueKind::NonFiniteValue => write!(f, "NaN or infinite value"),
+			IssueKind::InvalidIndex => write!(f, "invalid index"),
+			IssueKind::IndexOutOfRange => write!(f, "index out of range"),
+			IssueKind::SkippedReference => write!(f, "reference to a skipped element"),
+			IssueKind::MisplacedStatement => write!(f, "misplaced statement")
+		}
+	}
+}
+
+/// An invalid statement of an OBJ or MTL file, `column` being the one of the offending value.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Issue {
+	pub file: 		Rc<str>,
+	pub line: 		usize,
+	pub column: 	usize,
+	pub kind: 		IssueKind,
+	pub message: 	String
+}
+
+impl fmt::Display for Issue {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}:{}:{}: {}", self.file, self.line, self.column, self.message)
+	}
+}
+
+/// The result of a parsing: the parsed value and the skipped statements,
+/// or the first invalid statement in strict mode.
+pub type Checked<T> = Result<(T, Vec<Issue>), Issue>;
+
+/// An invalid value of a statement: the index of its chunk (the number of chunks
+/// when a value is missing), the kind of issue and its description.
+type Problem = (usize, IssueKind, String);
+
+/// Create the issue of a statement from one of its problems.
+fn issue(statement: &Statement, problem: Problem) -> Issue {
+
+	let (index, kind, message) = problem;
+	let (line, column) = statement.positions[index.min(statement.positions.len() - 1)];
+	
+	Issue { file: statement.file.clone(), line, column, kind, message }
+	
+}
+
+/// This function summarize the skipped statements by kind.
+///
+/// # Examples
+///
+/// ```
+/// let model = read_obj_checked(&read_statements("v 0 0 0\nf 1 2 3\nfoo\n".as_bytes(), ""), false);
+///
+/// assert!(summarize_issues(&model.1) == "2 statements were skipped (1 index out of range, 1 unknown statement)");
+/// ```
+pub fn summarize_issues(issues: &[Issue]) -> String {
+
+	let mut counts: Vec<(IssueKind, usize)> = Vec::new();
+	
+	for issue in issues {
+		match counts.iter_mut().find(|count| count.0 == issue.kind) {
+			Some(count) => count.1 += 1,
+			None => counts.push((issue.kind, 1))
+		}
+	}
+	
+	let details: Vec<String> = counts.iter().map(|&(kind, count)| format!("{} {}", count, kind)).collect();
+	
+	format!(
+		"{} statement{} skipped ({})",
+		issues.len(),
+		if issues.len() == 1 { " was" } else { "s were" },
+		details.join(", ")
+	)
+	
+}
+
+/// This function parse the OBJ file with the given name, the files it includes
+/// with `call` being read in place (see [expand_obj](./fn.expand_obj.html)).
+/// See [read_obj_checked](./fn.read_obj_checked.html) for details.
+///
+/// # Panics
+///
+/// Panics if the file can't be opened.
+pub fn parse_obj(file_name: & str, settings: &ParseSettings) -> Checked<Model> {
+
+	let file = match File::open(file_name) {
+		Ok(file) => file,
+		Err(_) => panic!("Couldn't open {}", file_name)
+	};
+	
+	let path = Path::new(file_name);
+	let mut stack = vec![path.canonicalize().unwrap_or_else(|_| path.to_path_buf())];
+	
+	let statements = expand_obj(BufReader::new(&file), file_name, &[], settings, &mut stack);
+	let (model, mut issues) = read_obj_checked(&statements, settings.strict);
+	
+	if settings.strict && !issues.is_empty() {
+		return Err(issues.swap_remove(0));
+	}
+	
+	Ok((model, issues))
+	
+}
+
+/// This function split an OBJ or MTL content into statements:
+///
+/// - a line ending with a backslash (out of a comment) is continued on the next line,
+/// - tokens are separated by spaces or tabs, and the line endings (`\n` or `\r\n`) are removed,
+/// - a token starting with `#` starts a comment, up to the end of the line (except the ZBrush
+///   `#MRGB` polypaint, stored as comments, which is a statement).
+///
+/// Each statement keeps the line it starts on, and the position of its tokens.
+/// Empty statements (blank lines and comments) are skipped.
+///
+/// # Examples
+///
+/// ```
+/// let statements = read_statements("# comment\r\nv 0 \\\r\n0\t0 # origin\r\nf 1 1 1\r\n".as_bytes(), "model.obj");
+///
+/// assert!(statements[0].text == "v 0 0 0" && statements[0].line == 2);
+/// assert!(statements[0].positions == vec![(2, 1), (2, 3), (3, 1), (3, 3), (3, 4)]);
+/// assert!(statements[1].text == "f 1 1 1" && statements[1].line == 4);
+/// ```
+pub fn read_statements<R: BufRead>(reader: R, file_name: &str) -> Vec<Statement> {
+
+	let file: Rc<str> = Rc::from(file_name);
+	let mut statements = Vec::new();
+	let mut text = String::new();
+	let mut positions = Vec::new();
+	let mut end = (0, 0);
+	let mut start = 0;
+	
+	for (number, line) in read_lines(reader).enumerate() {
+	
+		let line = line.trim_end_matches('\r');
+		let (line, continued) = match line.strip_suffix('\\') {
+			Some(line) => (line, true),
+			None => (line, false)
+		};
+		
+		if positions.is_empty() {
+			start = number + 1;
+		}
+		
+		let mut token = None;
+		let mut comment = false;
+		
+		for (column, (index, character)) in line.char_indices().chain(Some((line.len(), ' '))).enumerate() {
+			match token {
+				None if !character.is_whitespace() => {
+					if character == '#' && !(positions.is_empty() && line[index..].starts_with("#MRGB")) {
+						comment = true;
+						break;
+					}
+					token = Some((index, column));
+				}
+				Some((first, first_column)) if character.is_whitespace() => {
+					if !text.is_empty() {
+						text.push(' ');
+					}
+					text.push_str(&line[first..index]);
+					positions.push((number + 1, first_column + 1));
+					end = (number + 1, column + 1);
+					token = None;
+				}
+				_ => ()
+			}
+		}
+		
+		// a backslash ending a comment doesn't continue the statement
+		if (continued && !comment) || positions.is_empty() {
+			continue;
+		}
+		
+		positions.push(end);
+		statements.push(Statement { file: file.clone(), line: start, text: mem::take(&mut text), positions: mem::take(&mut positions) });
+		
+	}
+	
+	if !positions.is_empty() {
+		positions.push(end);
+		statements.push(Statement { file, line: start, text, positions });
+	}
+	
+	statements
+	
+}
+
+/// This function expand the `call` and `csh` statements of an OBJ content:
+///
+/// - `call file.obj arg1 arg2 ...` is replaced by the statements of the file (relative
+///   to the directory of `file_name`, the calling file), in which `$1`, `$2`... are replaced
+///   by the arguments. A file calling itself, directly or not (the files being read are
+///   in `stack`), is skipped with a message, as are the files that can't be found.
+/// - `csh command` (or `csh -command`, ignoring its errors) runs the command with `sh`
+///   when `settings.allow_csh` is set, and is skipped with a message otherwise.
+///
+/// Statements keep the file and the line they come from (see [read_statements](./fn.read_statements.html)).
+pub fn expand_obj<R: BufRead>(reader: R, file_name: &str, arguments: &[String], settings: &ParseSettings, stack: &mut Vec<PathBuf>) -> Vec<Statement> {
+
+	let directory = Path::new(file_name).parent().unwrap_or_else(|| Path::new(""));
+	let mut statements = Vec::new();
+	
+	for mut statement in read_statements(reader, file_name) {
+	
+		statement.text = substitute_arguments(&statement.text, arguments);
+		
+		let line = &statement.text;
+		let chunks: Vec<&str> = line.split_whitespace().collect();
+		
+		match chunks.first() {
+			Some(&"call") if chunks.len() >= 2 => {
+				let path = directory.join(chunks[1]);
+				let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+				
+				if stack.contains(&canonical) {
+					println!("[{}] is calling itself, the call is ignored", path.display());
+					continue;
+				}
+				
+				let file = match File::open(&path) {
+					Ok(file) => file,
+					Err(_) => {
+						println!("Couldn't find [{}]", path.display());
+						continue;
+					}
+				};
+				
+				let arguments: Vec<String> = chunks[2..].iter().map(|argument| argument.to_string()).collect();
+				
+				stack.push(canonical);
+				statements.extend(expand_obj(
+					BufReader::new(&file),
+					&path.to_string_lossy(),
+					&arguments,
+					settings,
+					stack
+				));
+				stack.pop();
+			}
+			Some(&"csh") if chunks.len() >= 2 => {
+				let command = line.trim_start()[3..].trim();
+				let (command, ignore_errors) = match command.strip_prefix('-') {
+					Some(command) => (command, true),
+					None => (command, false)
+				};
+				
+				if !settings.allow_csh {
+					println!("The shell command [{}] is ignored (use --allow-csh to run it)", command);
+					continue;
+				}
+				
+				let success = Command::new("sh").arg("-c").arg(command).status().map(|status| status.success()).unwrap_or(false);
+				
+				if !success && !ignore_errors {
+					println!("The shell command [{}] failed", command);
+				}
+			}
+			_ => statements.push(statement)
+		}
+		
+	}
+	
+	statements
+	
+}
+
+/// Replace the `$1`, `$2`... of a line by the given arguments,
+/// the ones without argument being kept.
+fn substitute_arguments(line: &str, arguments: &[String]) -> String {
+
+	if arguments.is_empty() {
+		return line.to_string();
+	}
+	
+	let mut result = String::new();
+	let mut characters = line.chars().peekable();
+	
+	while let Some(character) = characters.next() {
+	
+		if character != '$' {
+			result.push(character);
+			continue;
+		}
+		
+		let mut number = String::new();
+		
+		while let Some(&digit) = characters.peek().filter(|digit| digit.is_ascii_digit()) {
+			number.push(digit);
+			characters.next();
+		}
+		
+		match number.parse::<usize>().ok().filter(|&index| index >= 1 && index <= arguments.len()) {
+			Some(index) => result.push_str(&arguments[index - 1]),
+			None => {
+				result.push('$');
+				result.push_str(&number);
+			}
+		}
+	
+	}
+	
+	result
+	
+}
+
+/// This function read the vertices, normals, texture coordinates, faces,
+/// lines, points and materials of an OBJ content.
+/// A line ending with a backslash is continued on the next line.
+/// Invalid statements are skipped, see [read_obj_checked](./fn.read_obj_checked.html)
+/// (the converter uses [parse_obj](./fn.parse_obj.html), so we allow dead_code for this one).
+///
+/// # Examples
+///
+/// ```
+/// let model = read_obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n".as_bytes());
+///
+/// assert!(model.vertices.len() == 3);
+/// assert!(model.faces[0].vertex == vec![0, 1, 2]);
+/// ```
+#[allow(dead_code)]
+pub fn read_obj<R: BufRead>(reader: R) -> Model {
+
+	read_obj_checked(&read_statements(reader, ""), false).0
+	
+}
+
+/// This function read the statements of an OBJ content, and return the model
+/// with the issues found (one per invalid statement, its element being skipped):
+///
+/// - unknown statements, values which aren't numbers, NaN or infinite values and wrong numbers of values,
+/// - indices which are invalid (`0`, `1/2/3/4`) or out of the elements defined so far,
+///   and references to skipped elements (the vertices, texture coordinates and normals being still
+///   numbered, so the following indices are unchanged),
+/// - free-form statements out of a curve or a surface.
+///
+/// Empty indices are allowed (`f 1/ 2/ 3/` is the same as `f 1 2 3`).
+/// When `strict` is set, the reading stops at the first issue.
+///
+/// # Examples
+///
+/// ```
+/// let statements = read_statements("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\nf 1/ 2/ 3/\n".as_bytes(), "model.obj");
+/// let (model, issues) = read_obj_checked(&statements, false);
+///
+/// assert!(model.faces.len() == 1);
+/// assert!(issues[0].to_string() == "model.obj:4:7: vertex index 4 is out of range (3 defined)");
+/// ```
+pub fn read_obj_checked(statements: &[Statement], strict: bool) -> (Model, Vec<Issue>) {
+
+	let mut model 			= Model::default();
+	let mut issues 			= Vec::new();
+	let mut invalid: [HashSet<usize>; 3] = Default::default();
+	let mut material 		= 0;
+	let mut group 			= String::new();
+	let mut object 			= String::new();
+	let mut weights 		= Vec::new();
+	let mut cstype 			= None;
+	let mut degree 			= [0, 0];
+	let mut ctech 			= None;
+	let mut stech 			= None;
+	let mut free_form: Option<(bool, FreeForm)> = None;
+	let mut skipped_free_form = false;
+	let mut polypaint 		= Vec::new();
+	let mut material_name 	= None;
+	let mut map 			= None;
+	let mut display 		= Display::default();
+	
+	for statement in statements {
+	
+		let chunks: Vec<&str> = statement.text.split_whitespace().collect();
+		
+		if chunks.is_empty() {
+			continue;
+		}
+		
+		let counts = [model.vertices.len(), model.uvs.len(), model.normals.len()];
+		
+		let result = match chunks[0] {
+			// Vertices as (x,y,z[,w]) coordinates, w is the weight of rational curves and surfaces
+			// v 0.123 0.234 0.345 [1.0]
+			// or followed by a (r,g,b[,a]) color, components being between 0 and 1
+			// v 0.123 0.234 0.345 1.0 0.5 0.0 [1.0]
+			"v" => {
+				let values = count_values(&chunks, 3, 7).and_then(|_| parse_numbers(&chunks, 1)).and_then(|values| match values.len() {
+					5 => Err((chunks.len(), IssueKind::InvalidValue, "v expects 3 coordinates, and a weight or 3 or 4 color components".to_string())),
+					_ => Ok(values)
+				});
+				
+				match values {
+					Ok(values) => {
+						let color = match values.len() {
+							6 => Some([values[3], values[4], values[5], 1.0]),
+							7 => Some([values[3], values[4], values[5], values[6]]),
+							_ => None
+						};
+						
+						if let Some(color) = color {
+							model.colors.resize(model.vertices.len(), [1.0; 4]);
+							model.colors.push(color);
+						} else if !model.colors.is_empty() {
+							model.colors.push([1.0; 4]);
+						}
+						
+						model.vertices.push(Vertex { x: values[0], y: values[1], z: values[2] });
+						weights.push(if values.len() == 4 { values[3] } else { 1.0 });
+						Ok(())
+					}
+					Err(problem) => {
+						// a placeholder keeps the index of the following vertices until the end of the reading
+						invalid[0].insert(model.vertices.len());
+						
+						if !model.colors.is_empty() {
+							model.colors.push([1.0; 4]);
+						}
+						
+						model.vertices.push(Vertex { x: f64::NAN, y: f64::NAN, z: f64::NAN });
+						weights.push(1.0);
+						Err(problem)
+					}
+				}
+			}
+			// Normals in (x,y,z) form; normals might not be unit
+			// vn 0.707 0.000 0.707
+			"vn" => {
+				let values = count_values(&chunks, 3, 3).and_then(|_| parse_numbers(&chunks, 1));
+				
+				model.normals.push(match values {
+					Ok(ref values) => Vertex { x: values[0], y: values[1], z: values[2] },
+					Err(_) => {
+						invalid[2].insert(model.normals.len());
+						Vertex { x: 0.0, y: 0.0, z: 0.0 }
+					}
+				});
+				values.map(|_| ())
+			}
+			// Texture coordinates in (u[,v,w]) coordinates, v and w are optional (0 by default)
+			// vt 0.500 [-1.352] [0.234]
+			// Parameter space vertices in (u[,v,w]) coordinates, used by the trimming curves of surfaces,
+			// v is optional (0 by default) and w is the weight of rational curves (1 by default)
+			// vp 0.310 [1.234] [1.0]
+			"vt" | "vp" => {
+				let values = count_values(&chunks, 1, 3).and_then(|_| parse_numbers(&chunks, 1));
+				let default_w = if chunks[0] == "vp" { 1.0 } else { 0.0 };
+				
+				let uv = match values {
+					Ok(ref values) => Uv {
+						u: values[0],
+						v: *values.get(1).unwrap_or(&0.0),
+						w: *values.get(2).unwrap_or(&default_w)
+					},
+					Err(_) => {
+						if chunks[0] == "vt" {
+							invalid[1].insert(model.uvs.len());
+						}
+						Uv { u: 0.0, v: 0.0, w: default_w }
+					}
+				};
+				
+				if chunks[0] == "vt" {
+					model.uvs.push(uv);
+				} else {
+					model.parameter_vertices.push(uv);
+				}
+				values.map(|_| ())
+			}
+			// Face
+			// f 1 2 3
+			// f 1/1 2/2 3/3
+			// f 1/1/1 2/2/2 3/3/3
+			// f 1//1 2//2 3//3
+			// Line (polyline through the vertices)
+			// l 1 2 3
+			// l 1/1 2/2 3/3
+			// Points
+			// p 1 2 3
+			"f" | "l" | "p" => {
+				let minimum = match chunks[0] { "f" => 3, "l" => 2, _ => 1 };
+				
+				count_values(&chunks, minimum, usize::MAX)
+					.and_then(|_| resolve_references(&chunks, 1, counts, &invalid))
+					.map(|references| {
+						let mut element = Face {
+							material,
+							group: 		group.clone(),
+							object: 	object.clone(),
+							display,
+							..Face::default()
+						};
+						
+						for reference in references {
+							element.vertex.extend(reference[0]);
+							element.uv.extend(reference[1]);
+							if chunks[0] == "f" {
+								element.normal.extend(reference[2]);
+							}
+						}
+						
+						match chunks[0] {
+							"f" => model.faces.push(element),
+							"l" => model.lines.push(element),
+							_ => model.points.push(element)
+						}
+					})
+			}
+			// Free-form curve / surface type, optionally rational
+			// cstype [rat] bezier|bspline|cardinal|taylor|bmatrix
+			"cstype" => {
+				let rational = chunks.len() > 2 && chunks[1] == "rat";
+				
+				cstype = match chunks.last() {
+					Some(&"bezier") => Some((Basis::Bezier, rational)),
+					Some(&"bspline") => Some((Basis::BSpline, rational)),
+					Some(&"cardinal") => Some((Basis::Cardinal, rational)),
+					Some(&"taylor") => Some((Basis::Taylor, rational)),
+					Some(&"bmatrix") => Some((Basis::BMatrix, rational)),
+					_ => None
+				};
+				
+				match cstype {
+					Some(_) if chunks.len() == if rational { 3 } else { 2 } => Ok(()),
+					_ => Err((1, IssueKind::InvalidValue, "cstype expects [rat] bezier|bspline|cardinal|taylor|bmatrix".to_string()))
+				}
+			}
+			// Degree in u and v (v for surfaces only)
+			// deg 3 [3]
+			"deg" => {
+				count_values(&chunks, 1, 2).and_then(|_| {
+					for (i, chunk) in chunks.iter().enumerate().skip(1) {
+						degree[i - 1] = chunk.parse().map_err(|_| (i, IssueKind::InvalidValue, format!("{} isn't a degree", chunk)))?;
+					}
+					if chunks.len() == 2 {
+						degree[1] = 0;
+					}
+					Ok(())
+				})
+			}
+			// Curve and surface approximation techniques
+			// ctech cparm 4 | ctech cspace 0.5 | ctech curv 0.1 10
+			// stech cparma 4 6 | stech cparmb 4 | stech cspace 0.5 | stech curv 0.1 10
+			"ctech" | "stech" => {
+				count_values(&chunks, 2, 3).and_then(|_| parse_numbers(&chunks, 2)).and_then(|values| {
+					let technique = match (chunks[1], values.len()) {
+						("cparm", 1) | ("cparmb", 1) => Technique::Parametric(values[0], values[0]),
+						("cparma", 2) => Technique::Parametric(values[0], values[1]),
+						("cspace", 1) => Technique::Space(values[0]),
+						("curv", 2) => Technique::Curvature(values[0], values[1]),
+						_ => return Err((1, IssueKind::InvalidValue, format!("unknown {} technique {}", chunks[0], chunks[1])))
+					};
+					
+					if chunks[0] == "ctech" { ctech = Some(technique); } else { stech = Some(technique); }
+					Ok(())
+				})
+			}
+			// Free-form curve between the global parameters u0 and u1
+			// curv u0 u1 v1 v2 v3 ...
+			// Free-form surface between the parameters s0, s1 and t0, t1
+			// surf s0 s1 t0 t1 v1/vt1/vn1 v2/vt2/vn2 ...
+			"curv" | "surf" => {
+				let surface = chunks[0] == "surf";
+				let first = if surface { 5 } else { 3 };
+				
+				let element = count_values(&chunks, first, usize::MAX)
+					.and_then(|_| parse_numbers(&chunks[..first], 1))
+					.and_then(|range| {
+						let references = resolve_references(&chunks, first, counts, &invalid)?;
+						let (basis, rational) = cstype.ok_or_else(|| (0, IssueKind::MisplacedStatement, format!("{} without cstype", chunks[0])))?;
+						
+						Ok(FreeForm {
+							basis,
+							rational,
+							degree,
+							range: 		[range[0], range[1], *range.get(2).unwrap_or(&0.0), *range.get(3).unwrap_or(&0.0)],
+							control: 	references.iter().filter_map(|reference| reference[0]).collect(),
+							uvs: 		references.iter().filter_map(|reference| reference[1]).collect(),
+							weights: 	references.iter().filter_map(|reference| reference[0]).map(|index| weights[index]).collect(),
+							parameters: [Vec::new(), Vec::new()],
+							technique: 	if surface { stech } else { ctech },
+							trimmed: 	false,
+							material,
+							group: 		group.clone(),
+							object: 	object.clone(),
+							display
+						})
+					});
+				
+				// the statements of a skipped curve or surface are skipped with it
+				skipped_free_form = element.is_err();
+				
+				element.map(|element| free_form = Some((surface, element)))
+			}
+			// Global parameter values (knot vector or breakpoints) of the current curve or surface
+			// parm u 0.0 0.0 0.0 1.0 1.0 1.0
+			"parm" => {
+				match free_form {
+					Some((_, ref mut element)) => count_values(&chunks, 2, usize::MAX).and_then(|_| parse_numbers(&chunks, 2)).map(|values| {
+						let direction = if chunks[1] == "v" { 1 } else { 0 };
+						
+						element.parameters[direction].extend(values);
+					}),
+					None if skipped_free_form => Ok(()),
+					None => Err((0, IssueKind::MisplacedStatement, "parm out of a curve or a surface".to_string()))
+				}
+			}
+			// Trimming and hole loops of the current surface
+			// trim u0 u1 curv2d ...
+			"trim" | "hole" => {
+				match free_form {
+					Some((_, ref mut element)) => {
+						element.trimmed = true;
+						Ok(())
+					}
+					None if skipped_free_form => Ok(()),
+					None => Err((0, IssueKind::MisplacedStatement, format!("{} out of a surface", chunks[0])))
+				}
+			}
+			// End of the current curve or surface
+			"end" => {
+				let skipped = mem::replace(&mut skipped_free_form, false);
+				
+				match free_form.take() {
+					Some((true, element)) => {
+						model.surfaces.push(element);
+						Ok(())
+					}
+					Some((false, element)) => {
+						model.curves.push(element);
+						Ok(())
+					}
+					None if skipped => Ok(()),
+					None => Err((0, IssueKind::MisplacedStatement, "end out of a curve or a surface".to_string()))
+				}
+			}
+			// ZBrush polypaint, as (mask,r,g,b) hexadecimal bytes, one per vertex in the order of the v statements
+			// #MRGB 00ff0000 00ff8000 ... (or all the colors in a single chunk)
+			"#MRGB" => {
+				for chunk in &chunks[1..] {
+					polypaint.extend(chunk.as_bytes().chunks(8).map(parse_mrgb));
+				}
+				Ok(())
+			}
+			// Groups, the elements going to the first one
+			// g group_name [other_group_name...]
+			"g" => {
+				group = chunks.get(1).map_or(String::new(), |name| name.to_string());
+				Ok(())
+			}
+			// Object
+			// o object_name
+			"o" => {
+				object = chunks[1..].join(" ");
+				Ok(())
+			}
+			// Materials definition
+			// mtllib file.mtl
+			"mtllib" => {
+				count_values(&chunks, 1, 1).map(|_| model.mtllib = chunks[1].to_string())
+			}
+			// Level of detail of the following elements, from 1 to 100 (0 turns it off)
+			// lod 50
+			"lod" => {
+				count_values(&chunks, 1, 1).and_then(|_| match chunks[1].parse::<u32>() {
+					Ok(lod) => {
+						display.lod = lod.min(100);
+						Ok(())
+					}
+					Err(_) => Err((1, IssueKind::InvalidValue, format!("{} isn't a level of detail", chunks[1])))
+				})
+			}
+			// Interpolations of the following elements
+			// bevel on|off
+			// c_interp on|off
+			// d_interp on|off
+			"bevel" | "c_interp" | "d_interp" => {
+				count_values(&chunks, 1, 1).and_then(|_| {
+					let on = match chunks[1] {
+						"on" => true,
+						"off" => false,
+						_ => return Err((1, IssueKind::InvalidValue, format!("{} expects on or off", chunks[0])))
+					};
+					
+					match chunks[0] {
+						"bevel" => display.bevel = on,
+						"c_interp" => display.color_interpolation = on,
+						_ => display.dissolve_interpolation = on
+					}
+					Ok(())
+				})
+			}
+			// Objects used for shadows and ray tracing
+			// shadow_obj shadow.obj
+			// trace_obj trace.obj
+			"shadow_obj" => {
+				count_values(&chunks, 1, 1).map(|_| model.shadow_obj = chunks[1].to_string())
+			}
+			"trace_obj" => {
+				count_values(&chunks, 1, 1).map(|_| model.trace_obj = chunks[1].to_string())
+			}
+			// Texture map libraries
+			// maplib file1.mpc file2.mpc
+			"maplib" => {
+				model.maplibs.extend(chunks[1..].iter().map(|chunk| chunk.to_string()));
+				Ok(())
+			}
+			// Material and texture map: each (material, texture map) pair is a material of the model
+			// usemtl material_name
+			// usemap map_name|off
+			"usemtl" | "usemap" => {
+				let name = if chunks.len() > 1 { chunks[1] } else { "" };
+				
+				if chunks[0] == "usemtl" {
+					material_name = Some(name.to_string());
+				} else {
+					map = if name == "off" { None } else { Some(name.to_string()) };
+				}
+				
+				// until a material or a map is used, faces use the first material
+				if material_name.is_none() && map.is_none() {
+					continue;
+				}
+				
+				let name = material_name.clone().unwrap_or_default();
+				
+				material = match (0..model.materials.len()).find(|&i| model.materials[i] == name && model.maps[i] == map) {
+					Some(index) => index,
+					None => {
+						model.materials.push(name);
+						model.maps.push(map.clone());
+						model.materials.len() - 1
+					}
+				};
+				Ok(())
+			}
+			// Statements without effect on the conversion: smoothing and merging groups, the trimming curves
+			// of the surfaces (in the parameter space), connectivity, and the statements expanded by expand_obj
+			"s" | "mg" | "sp" | "scrv" | "curv2" | "con" | "bmat" | "step" | "res" | "call" | "csh" => Ok(()),
+			keyword => Err((0, IssueKind::UnknownStatement, format!("unknown statement {}", keyword)))
+		};
+		
+		if let Err(problem) = result {
+			issues.push(issue(statement, problem));
+			
+			if strict {
+				break;
+			}
+		}
+		
+	}
+	
+	if !polypaint.is_empty() {
+		model.colors.resize(model.vertices.len(), [1.0; 4]);
+		
+		for (color, paint) in model.colors.iter_mut().zip(polypaint) {
+			if let Some(paint) = paint {
+				*color = paint;
+			}
+		}
+	}
+	
+	// the elements using a skipped vertex were skipped too
+	if !invalid[0].is_empty() {
+		remove_vertices(&mut model, &invalid[0]);
+	}
+	
+	(model, issues)
+	
+}
+
+/// Remove the given vertices (with their colors) from the model, renumbering the vertices
+/// of its elements, none of them using a removed vertex.
+fn remove_vertices(model: &mut Model, removed: &HashSet<usize>) {
+
+	let mut indices = Vec::with_capacity(model.vertices.len());
+	let mut kept = 0;
+	
+	for index in 0..model.vertices.len() {
+		indices.push(kept);
+		if !removed.contains(&index) {
+			kept += 1;
+		}
+	}
+	
+	model.vertices = model.vertices.iter().enumerate().filter(|&(index, _)| !removed.contains(&index)).map(|(_, vertex)| *vertex).collect();
+	model.colors = model.colors.iter().enumerate().filter(|&(index, _)| !removed.contains(&index)).map(|(_, color)| *color).collect();
+	
+	for element in model.faces.iter_mut().chain(model.lines.iter_mut()).chain(model.points.iter_mut()) {
+		for index in &mut element.vertex {
+			*index = indices[*index];
+		}
+	}
+	
+	for free_form in model.curves.iter_mut().chain(model.surfaces.iter_mut()) {
+		for index in &mut free_form.control {
+			*index = indices[*index];
+		}
+	}
+	
+}
+
+/// This function return the display and render statements used by the model
+/// (`lod`, `bevel`, `c_interp`, `d_interp`, `shadow_obj` and `trace_obj`),
+/// so the ones which can't be exported may be reported.
+///
+/// # Examples
+///
+/// ```
+/// let model = read_obj("v 0 0 0\nlod 10\np 1\ntrace_obj trace.obj\n".as_bytes());
+///
+/// assert!(render_directives(&model) == vec!["lod", "trace_obj"]);
+/// ```
+pub fn render_directives(model: &Model) -> Vec<&'static str> {
+
+	let displays: Vec<&Display> = model.faces.iter()
+		.chain(&model.lines)
+		.chain(&model.points)
+		.map(|element| &element.display)
+		.collect();
+	
+	let used = [
+		("lod", displays.iter().any(|display| display.lod > 0)),
+		("bevel", displays.iter().any(|display| display.bevel)),
+		("c_interp", displays.iter().any(|display| display.color_interpolation)),
+		("d_interp", displays.iter().any(|display| display.dissolve_interpolation)),
+		("shadow_obj", !model.shadow_obj.is_empty()),
+		("trace_obj", !model.trace_obj.is_empty())
+	];
+	
+	used.iter().filter(|directive| directive.1).map(|directive| directive.0).collect()
+	
+}
+
+/// Parse one ZBrush polypaint color (`MMRRGGBB` in hexadecimal, the mask being ignored),
+/// `None` if it isn't a valid color.
+fn parse_mrgb(mrgb: &[u8]) -> Option<[f64; 4]> {
+
+	let mrgb = ::std::str::from_utf8(mrgb).ok().filter(|mrgb| mrgb.len() == 8)?;
+	let component = |i: usize| u8::from_str_radix(&mrgb[i..i + 2], 16).map(|value| value as f64 / 255.0);
+	
+	match (component(2), component(4), component(6)) {
+		(Ok(r), Ok(g), Ok(b)) => Some([r, g, b, 1.0]),
+		_ => None
+	}
+	
+}
+
+/// Check the number of values of a statement (the chunks following its keyword).
+fn count_values(chunks: &[&str], min: usize, max: usize) -> Result<(), Problem> {
+
+	let count = chunks.len() - 1;
+	
+	if count >= min && count <= max {
+		return Ok(());
+	}
+	
+	let expected = if max == usize::MAX {
+		format!("at least {}", min)
+	} else if min == max {
+		min.to_string()
+	} else {
+		format!("{} to {}", min, max)
+	};
+	
+	Err((
+		if count < min { chunks.len() } else { max + 1 },
+		IssueKind::InvalidValue,
+		format!("{} expects {} values, found {}", chunks[0], expected, count)
+	))
+	
+}
+
+/// Parse the values of a statement from its chunk `first`,
+/// as finite numbers.
+fn parse_numbers(chunks: &[&str], first: usize) -> Result<Vec<f64>, Problem> {
+
+	chunks.iter().enumerate().skip(first).map(|(i, chunk)| match chunk.parse::<f64>() {
+		Ok(value) if value.is_finite() => Ok(value),
+		Ok(_) => Err((i, IssueKind::NonFiniteValue, format!("{} isn't a finite number", chunk))),
+		Err(_) => Err((i, IssueKind::InvalidValue, format!("{} isn't a number", chunk)))
+	}).collect()
+	
+}
+
+/// Resolve the `v/vt/vn` references of an element from its chunk `first`, against the numbers of
+/// vertices, texture coordinates and normals defined so far (`counts`) and the skipped ones (`invalid`).
+/// The vertex index is required, the other ones may be empty (`1//1`, `1/`).
+fn resolve_references(chunks: &[&str], first: usize, counts: [usize; 3], invalid: &[HashSet<usize>; 3]) -> Result<Vec<[Option<usize>; 3]>, Problem> {
+
+	const NAMES: [&str; 3] = ["vertex", "texture coordinate", "normal"];
+	
+	let mut references = Vec::new();
+	
+	for (i, chunk) in chunks.iter().enumerate().skip(first) {
+	
+		let mut reference = [None; 3];
+		
+		for (component, index) in chunk.split('/').enumerate() {
+			if component >= 3 {
+				return Err((i, IssueKind::InvalidIndex, format!("{} has more than 3 indices", chunk)));
+			}
+			if index.is_empty() && component > 0 {
+				continue;
+			}
+			
+			let resolved = match resolve_index(index, counts[component]) {
+				Some(resolved) => resolved,
+				None if index.parse::<i64>().is_ok_and(|index| index != 0) => return Err((
+					i,
+					IssueKind::IndexOutOfRange,
+					format!("{} index {} is out of range ({} defined)", NAMES[component], index, counts[component])
+				)),
+				None => return Err((i, IssueKind::InvalidIndex, format!("{} isn't a {} index", index, NAMES[component])))
+			};
+			
+			if invalid[component].contains(&resolved) {
+				return Err((i, IssueKind::SkippedReference, format!("{} {} was skipped", NAMES[component], index)));
+			}
+			
+			reference[component] = Some(resolved);
+		}
+		
+		references.push(reference);
+	
+	}
+	
+	Ok(references)
+	
+}
+
+/// Convert an OBJ index into a zero-based index, `None` if it isn't an index
+/// of the `count` elements defined so far.
+/// OBJ indices start at 1, and negative indices are relative
+/// to the end of the elements defined so far (`-1` being the last one).
+fn resolve_index(index: &str, count: usize) -> Option<usize> {
+
+	let index: i64 = match index.parse() {
+		Ok(index) => index,
+		Err(_) => return None
+	};
+	
+	if index > 0 && index <= count as i64 {
+		Some((index - 1) as usize)
+	} else if index < 0 && -index <= count as i64 {
+		Some((count as i64 + index) as usize)
+	} else {
+		None
+	}
+	
+}
+
+/// Debug colors used for materials that are not defined in a MTL file.
+pub const COLORS: [u32; 7] = [0xeeeeee, 0xee0000, 0x00ee00, 0x0000ee, 0xeeee00, 0x00eeee, 0xee00ee];
+
+/// This function convert a color with components between 0.0 and 1.0 into
+/// its hexadecimal representation (`0xRRGGBB`).
+///
+/// # Examples
+///
+/// ```
+/// assert!(hex_color(&[1.0, 0.5, 0.0]) == 0xff7f00);
+/// ```
+pub fn hex_color(color: &[f64; 3]) -> u32 {
+
+	let component = |value: f64| (value.clamp(0.0, 1.0) * 255.0) as u32;
+	
+	(component(color[0]) << 16) + (component(color[1]) << 8) + component(color[2])
+	
+}
+
+/// This function format a floating point number for the JSON output.
+/// With a precision, the number is rounded to that many decimals,
+/// otherwise it is written with 6 significant digits.
+/// Useless trailing zeros are removed in both cases.
+///
+/// # Examples
+///
+/// ```
+/// assert!(format_float(1.0, None) == "1");
+/// assert!(format_float(0.123456789, None) == "0.123457");
+/// assert!(format_float(0.123456789, Some(2)) == "0.12");
+/// assert!(format_float(-0.0001, Some(2)) == "0");
+/// ```
+pub fn format_float(value: f64, precision: Option<usize>) -> String {
+
+	let decimals = match precision {
+		Some(decimals) => decimals,
+		None if value == 0.0 || !value.is_finite() => 0,
+		None => (5 - value.abs().log10().floor() as i32).max(0) as usize
+	};
+	
+	let mut text = format!("{:.*}", decimals, value);
+	
+	if text.contains('.') {
+		while text.ends_with('0') {
+			text.pop();
+		}
+		if text.ends_with('.') {
+			text.pop();
+		}
+	}
+	
+	if text == "-0" {
+		text = "0".to_string();
+	}
+	
+	text
+	
+}
+
+/// This function generate the JSON representation of one vertex.
+/// When a scale is given, the vertex is scaled and truncated to integers,
+/// the loader divides the coordinates by the `scale` of the model.
+///
+/// # Examples
+///
+/// ```
+/// let vertex = Vertex { x: 0.123, y: -1.5, z: 2.0 };
+///
+/// assert!(generate_vertex(&vertex, None, None) == "0.123,-1.5,2");
+/// assert!(generate_vertex(&vertex, None, Some(1)) == "0.1,-1.5,2");
+/// assert!(generate_vertex(&vertex, Some(10.0), None) == "1,-15,20");
+/// ```
+pub fn generate_vertex(vertex: &Vertex, scale: Option<f64>, precision: Option<usize>) -> String {
+
+	match scale {
+		Some(scale) => format!(
+			"{},{},{}",
+			(vertex.x * scale) as i64,
+			(vertex.y * scale) as i64,
+			(vertex.z * scale) as i64
+		),
+		None => format!(
+			"{},{},{}",
+			format_float(vertex.x, precision),
+			format_float(vertex.y, precision),
+			format_float(vertex.z, precision)
+		)
+	}
+	
+}
+
+/// This function generate the JSON representation of one normal.
+pub fn generate_normal(normal: &Vertex, precision: Option<usize>) -> String {
+
+	format!(
+		"{},{},{}",
+		format_float(normal.x, precision),
+		format_float(normal.y, precision),
+		format_float(normal.z, precision)
+	)
+	
+}
+
+/// This function generate the JSON representation of one texture coordinate,
+/// the ascii format only supports 2D coordinates so `w` is dropped.
+pub fn generate_uv(uv: &Uv, precision: Option<usize>) -> String {
+
+	format!(
+		"{},{}",
+		format_float(uv.u, precision),
+		format_float(uv.v, precision)
+	)
+	
+}
+
+/// This function generate the JSON representation of one face.
+/// The first value is the face type, a bitmask of:
+///
+/// - bit 0: isQuad
+/// - bit 1: hasMaterial
+/// - bit 2: hasFaceUv
+/// - bit 3: hasFaceVertexUv
+/// - bit 4: hasFaceNormal
+/// - bit 5: hasFaceVertexNormal
+/// - bit 6: hasFaceColor
+/// - bit 7: hasFaceVertexColor
+///
+/// followed by the vertex indices, the material index, the vertex uvs indices,
+/// the vertex normals indices and the colors indices, the order expected by the JSONLoader.
+/// `colors` holds the color index of the face (bit 6), or the color index of each of its vertices (bit 7),
+/// and is empty when the model has no colors.
+/// Only triangles and quads are supported, see [triangulate](./fn.triangulate.html).
+///
+/// # Examples
+///
+/// ```
+/// let face = Face { vertex: vec![0, 1, 2], uv: vec![], normal: vec![3, 4, 5], material: 1, ..Face::default() };
+///
+/// assert!(generate_face(&face, true, &[]) == "34,0,1,2,1,3,4,5");
+/// assert!(generate_face(&face, false, &[]) == "2,0,1,2,1");
+/// assert!(generate_face(&face, false, &[0, 1, 2]) == "130,0,1,2,1,0,1,2");
+/// assert!(generate_face(&face, false, &[3]) == "66,0,1,2,1,3");
+/// ```
+pub fn generate_face(face: &Face, smooth: bool, colors: &[usize]) -> String {
+
+	let vertices 					= face.vertex.len();
+	let is_quad 					= vertices == 4;
+	let has_face_vertex_uvs 		= face.uv.len() >= vertices;
+	let has_face_vertex_normals 	= face.normal.len() >= vertices && smooth;
+	let has_face_color 				= colors.len() == 1;
+	let has_face_vertex_colors 		= colors.len() >= vertices;
+	
+	let mut face_type = 2; // hasMaterial, OBJs without materials get the default material
+	
+	if is_quad 					{ face_type |= 1; }
+	if has_face_vertex_uvs 		{ face_type |= 1 << 3; }
+	if has_face_vertex_normals 	{ face_type |= 1 << 5; }
+	if has_face_color 			{ face_type |= 1 << 6; }
+	if has_face_vertex_colors 	{ face_type |= 1 << 7; }
+	
+	let mut face_data = vec![face_type];
+	
+	face_data.extend(&face.vertex);
+	face_data.push(face.material);
+	
+	if has_face_vertex_uvs {
+		face_data.extend(&face.uv[..vertices]);
+	}
+	if has_face_vertex_normals {
+		face_data.extend(&face.normal[..vertices]);
+	}
+	if has_face_color || has_face_vertex_colors {
+		face_data.extend(colors);
+	}
+	
+	face_data.iter().map(|value| value.to_string()).collect::<Vec<String>>().join(",")
+	
+}
+
+/// This function split the polygons having more than four vertices into triangles
+/// (triangle fan around the first vertex), so they can be written by
+/// [generate_face](./fn.generate_face.html). Polygons having less than three
+/// vertices are dropped.
+///
+/// # Examples
+///
+/// ```
+/// let face = Face { vertex: vec![0, 1, 2, 3, 4], uv: vec![], normal: vec![], material: 0, ..Face::default() };
+/// let faces = triangulate(&[face]);
+///
+/// assert!(faces.len() == 3);
+/// assert!(faces[2].vertex == vec![0, 3, 4]);
+/// ```
+pub fn triangulate(faces: &[Face]) -> Vec<Face> {
+
+	let mut triangulated = Vec::new();
+	
+	for face in faces {
+		let vertices = face.vertex.len();
+		
+		if vertices == 3 || vertices == 4 {
+			triangulated.push(face.clone());
+		} else if vertices > 4 {
+			let pick = |indices: &Vec<usize>, i: usize| -> Vec<usize> {
+				if indices.len() >= vertices {
+					vec![indices[0], indices[i], indices[i + 1]]
+				} else {
+					Vec::new()
+				}
+			};
+			for i in 1..vertices - 1 {
+				triangulated.push(Face {
+					vertex: 	pick(&face.vertex, i),
+					uv: 		pick(&face.uv, i),
+					normal: 	pick(&face.normal, i),
+					material: 	face.material,
+					group: 		face.group.clone(),
+					object: 	face.object.clone(),
+					display: 	face.display
+				});
+			}
+		}
+	}
+	
+	triangulated
+	
+}
+
+/// Escape a string for the JSON output.
+pub fn json_string(value: &str) -> String {
+
+	format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+	
+}
+
+/// Format a color for the JSON output.
+fn json_color(color: &[f64; 3]) -> String {
+
+	format!(
+		"[{}, {}, {}]",
+		format_float(color[0], None),
+		format_float(color[1], None),
+		format_float(color[2], None)
+	)
+	
+}
+
+/// This function list the properties of the material with the given index,
+/// as (name, JSON value) pairs sorted by name.
+/// Debug properties are always set, the other ones only when defined in the MTL file
+/// (the PBR statements have no equivalent in the JSON model format, except the normal map).
+/// The illumination model selects the shading of the loader: `basic` for the constant color (0),
+/// `lambert` for the diffuse one (1) and `phong` for the others.
+/// Transparent materials are blended without depth writes, and the loader having no alpha test,
+/// alpha textures are blended too (see [alpha_mode](./fn.alpha_mode.html)).
+pub fn material_properties(material: &Material, index: usize) -> Vec<(String, String)> {
+
+	let mut properties = vec![
+		("DbgName".to_string(), json_string(&material.name)),
+		("DbgIndex".to_string(), index.to_string()),
+		("DbgColor".to_string(), COLORS[index % COLORS.len()].to_string())
+	];
+	
+	{
+		let mut color = |name: &str, value: &Option<[f64; 3]>| {
+			if let Some(ref value) = *value {
+				properties.push((name.to_string(), json_color(value)));
+			}
+		};
+		color("colorAmbient", &material.color_ambient);
+		color("colorDiffuse", &material.color_diffuse);
+		color("colorSpecular", &material.color_specular);
+	}
+	
+	{
+		let mut map = |name: &str, value: &Option<String>| {
+			if let Some(ref value) = *value {
+				properties.push((name.to_string(), json_string(value)));
+			}
+		};
+		map("mapAmbient", &material.map_ambient);
+		map("mapDiffuse", &material.map_diffuse);
+		map("mapSpecular", &material.map_specular);
+		map("mapBump", &material.map_bump);
+		map("mapNormal", &material.map_normal);
+		map("mapAlpha", alpha_map(material));
+	}
+	
+	if let Some(value) = material.specular_coef {
+		properties.push(("specularCoef".to_string(), format_float(value, None)));
+	}
+	if let Some(value) = material.transparency {
+		properties.push(("transparency".to_string(), format_float(value, None)));
+	}
+	match alpha_mode(material) {
+		AlphaMode::Blend => {
+			properties.push(("transparent".to_string(), "true".to_string()));
+			properties.push(("depthWrite".to_string(), "false".to_string()));
+		}
+		AlphaMode::Mask => properties.push(("transparent".to_string(), "true".to_string())),
+		AlphaMode::Opaque => ()
+	}
+	if let Some(value) = material.optical_density {
+		properties.push(("opticalDensity".to_string(), format_float(value, None)));
+	}
+	if let Some(value) = material.illumination {
+		properties.push(("illumination".to_string(), value.to_string()));
+		properties.push(("shading".to_string(), json_string(match value {
+			0 => "basic",
+			1 => "lambert",
+			_ => "phong"
+		})));
+	}
+	if let Some(value) = material.reflectivity {
+		properties.push(("reflectivity".to_string(), format_float(value, None)));
+	}
+	
+	properties.sort();
+	
+	properties
+	
+}
+
+/// This function resolve the materials used by the model:
+/// materials defined in the MTL file are used when they exist,
+/// otherwise a default material is created with a debug color.
+/// A material used with a texture map (`usemap`) is a variant named `material_map`, whose
+/// diffuse texture is the image of the map in the map libraries (`maps`), or the map name itself.
+/// When the model doesn't use any material, a single `default` material is returned.
+/// The returned materials are sorted by index.
+pub fn create_materials(model: &Model, mtl: &[Material], maps: &HashMap<String, String>) -> Vec<Material> {
+
+	let default = ["default".to_string()];
+	let names = if model.materials.is_empty() { &default[..] } else { &model.materials[..] };
+	
+	names.iter().enumerate().map(|(index, name)| {
+		let mut material = match mtl.iter().find(|material| &material.name == name) {
+			Some(material) => material.clone(),
+			None => Material { name: name.clone(), ..Material::default() }
+		};
+		
+		if let Some(Some(map)) = model.maps.get(index) {
+			material.name = format!("{}_{}", material.name, map);
+			material.map_diffuse = Some(maps.get(map).unwrap_or(map).clone());
+		}
+		
+		material
+	}).collect()
+	
+}
+
+/// This function return the color baked for the material with the given index (`-b`):
+/// its diffuse color, or its debug color when the MTL file doesn't define it.
+///
+/// # Examples
+///
+/// ```
+/// assert!(material_color(&Material { color_diffuse: Some([1.0, 0.5, 0.0]), ..Material::default() }, 1) == [1.0, 0.5, 0.0]);
+/// assert!(material_color(&Material::default(), 1) == [238.0 / 255.0, 0.0, 0.0]);
+/// ```
+pub fn material_color(material: &Material, index: usize) -> [f64; 3] {
+
+	match material.color_diffuse {
+		Some(color) => color,
+		None => {
+			let color = COLORS[index % COLORS.len()];
+			[
+				((color >> 16) & 0xff) as f64 / 255.0,
+				((color >> 8) & 0xff) as f64 / 255.0,
+				(color & 0xff) as f64 / 255.0
+			]
+		}
+	}
+	
+}
+
+/// This function return the single material of a model whose material colors are baked:
+/// a white material, multiplied by the baked colors.
+pub fn baked_material() -> Material {
+
+	Material { name: "baked".to_string(), color_diffuse: Some([1.0, 1.0, 1.0]), ..Material::default() }
+	
+}
+
+/// This function bake the material colors into the vertex colors of the model (`-b`):
+/// each vertex gets the color of the material of the elements using it, multiplied by its own color,
+/// and is duplicated for each other material using it. All the elements then use a single
+/// material (see [baked_material](./fn.baked_material.html)), which is returned.
+///
+/// # Examples
+///
+/// ```
+/// let mut model = read_obj("v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nusemtl red\nf 1 2 3\nusemtl blue\nf 1 3 4\n".as_bytes());
+/// let materials = [
+///     Material { color_diffuse: Some([1.0, 0.0, 0.0]), ..Material::default() },
+///     Material { color_diffuse: Some([0.0, 0.0, 1.0]), ..Material::default() }
+/// ];
+///
+/// bake_vertex_colors(&mut model, &materials);
+///
+/// assert!(model.vertices.len() == 6);
+/// assert!(model.faces[1].vertex == vec![4, 5, 3]);
+/// assert!(model.colors[4] == [0.0, 0.0, 1.0, 1.0]);
+/// ```
+pub fn bake_vertex_colors(model: &mut Model, materials: &[Material]) -> Vec<Material> {
+
+	let count = model.vertices.len();
+	let colors = mem::replace(&mut model.colors, vec![[1.0; 4]; count]);
+	
+	// material whose color each vertex holds, and the copies of the vertices for the other materials
+	let mut owners: Vec<Option<usize>> = vec![None; count];
+	let mut copies: HashMap<(usize, usize), usize> = HashMap::new();
+	
+	for element in model.faces.iter_mut().chain(model.lines.iter_mut()).chain(model.points.iter_mut()) {
+	
+		let material = element.material.min(materials.len() - 1);
+		let color = material_color(&materials[material], material);
+		
+		for vertex in element.vertex.iter_mut().filter(|vertex| **vertex < count) {
+		
+			let original = colors.get(*vertex).cloned().unwrap_or([1.0; 4]);
+			let baked = [color[0] * original[0], color[1] * original[1], color[2] * original[2], original[3]];
+			
+			match owners[*vertex] {
+				None => {
+					owners[*vertex] = Some(material);
+					model.colors[*vertex] = baked;
+				}
+				Some(owner) if owner == material => (),
+				Some(_) => {
+					*vertex = match copies.get(&(*vertex, material)) {
+						Some(&copy) => copy,
+						None => {
+							let position = model.vertices[*vertex];
+							model.vertices.push(position);
+							model.colors.push(baked);
+							copies.insert((*vertex, material), model.vertices.len() - 1);
+							model.vertices.len() - 1
+						}
+					};
+				}
+			}
+		
+		}
+		
+		element.material = 0;
+	
+	}
+	
+	model.materials = vec!["baked".to_string()];
+	model.maps = vec![None];
+	
+	vec![baked_material()]
+	
+}
+
+/// This function load the MTL file referenced by the model, relatively to the OBJ file.
+/// Returns an empty list (with a message) when the file can't be found.
+/// See [parse_mtl](./fn.parse_mtl.html) for details.
+pub fn load_mtl(model: &Model, obj_file_name: &str, settings: &ParseSettings) -> Checked<Vec<Material>> {
+
+	if model.mtllib.is_empty() {
+		return Ok((Vec::new(), Vec::new()));
+	}
+	
+	let path = match Path::new(obj_file_name).parent() {
+		Some(parent) => parent.join(&model.mtllib),
+		None => PathBuf::from(&model.mtllib)
+	};
+	
+	if !path.is_file() {
+		println!("Couldn't find [{}]", path.display());
+		return Ok((Vec::new(), Vec::new()));
+	}
+	
+	parse_mtl(&path.to_string_lossy(), settings.strict)
+	
+}
+
+/// This function parse the texture map library with the given name.
+/// See [read_maplib](./fn.read_maplib.html) for details.
+///
+/// # Panics
+///
+/// Panics if the file can't be opened.
+pub fn parse_maplib(file_name: &str) -> HashMap<String, String> {
+
+	let file = match File::open(file_name) {
+		Ok(file) => file,
+		Err(_) => panic!("Couldn't open {}", file_name)
+	};
+	
+	read_maplib(BufReader::new(&file))
+	
+}
+
+/// This function read the lines of the given reader, without their line ending (`\n` or `\r\n`).
+/// Invalid UTF-8 sequences (like a Latin-1 comment) are replaced instead of ending the reading.
+fn read_lines<R: BufRead>(mut reader: R) -> impl Iterator<Item = String> {
+
+	::std::iter::from_fn(move || {
+		let mut bytes = Vec::new();
+		
+		match reader.read_until(b'\n', &mut bytes) {
+			Ok(0) | Err(_) => None,
+			Ok(_) => {
+				if bytes.last() == Some(&b'\n') {
+					bytes.pop();
+					if bytes.last() == Some(&b'\r') {
+						bytes.pop();
+					}
+				}
+				Some(String::from_utf8_lossy(&bytes).into_owned())
+			}
+		}
+	})
+	
+}
+
+/// This function read the texture maps of a map library, as map names with their image.
+/// Each map is defined by a `newmap` statement followed by its image, given like
+/// a MTL texture (`map [options] file`, or `map_Kd [options] file`), whose path is kept as is.
+///
+/// # Examples
+///
+/// ```
+/// let maps = read_maplib("newmap wood\nmap -s 2 2 1 textures/wood.png\n".as_bytes());
+///
+/// assert!(maps["wood"] == "textures/wood.png");
+/// ```
+pub fn read_maplib<R: BufRead>(reader: R) -> HashMap<String, String> {
+
+	let mut maps = HashMap::new();
+	let mut name = None;
+	
+	for line in read_lines(reader) {
+	
+		let chunks: Vec<&str> = line.split_whitespace().collect();
+		
+		match chunks.first() {
+			Some(&"newmap") if chunks.len() >= 2 => name = Some(chunks[1..].join(" ")),
+			Some(&"map") | Some(&"map_Kd") if chunks.len() >= 2 => {
+				if let Some(name) = name.take() {
+					maps.insert(name, chunks[chunks.len() - 1].to_string());
+				}
+			}
+			_ => ()
+		}
+	
+	}
+	
+	maps
+	
+}
+
+/// This function load the texture map libraries referenced by the model, relatively to the OBJ file.
+/// Libraries that can't be found are skipped (with a message).
+pub fn load_maplibs(model: &Model, obj_file_name: &str) -> HashMap<String, String> {
+
+	let mut maps = HashMap::new();
+	
+	for maplib in &model.maplibs {
+	
+		let path = match Path::new(obj_file_name).parent() {
+			Some(parent) => parent.join(maplib),
+			None => PathBuf::from(maplib)
+		};
+		
+		if !path.is_file() {
+			println!("Couldn't find [{}]", path.display());
+			continue;
+		}
+		
+		maps.extend(parse_maplib(&path.to_string_lossy()));
+	
+	}
+	
+	maps
+	
+}
+
+/// Number of decimals written for vertices, normals and uvs.
+/// `None` keeps the default output, which is 6 significant digits.
+#[derive(PartialEq, Copy, Clone, Default)]
+pub struct Precision {
+	pub vertices: 	Option<usize>,
+	pub normals: 	Option<usize>,
+	pub uvs: 		Option<usize>
+}
+
+/// A precision may be given as a single number of decimals, used for
+/// vertices, normals and uvs, or as three comma separated numbers
+/// for `vertices,normals,uvs`.
+impl FromStr for Precision {
+	type Err = ();
+	fn from_str(src: &str) -> Result<Precision, ()> {
+		let decimals: Vec<usize> = match src.split(',').map(|chunk| chunk.trim().parse()).collect() {
+			Ok(decimals) => decimals,
+			Err(_) => return Err(())
+		};
+		match decimals.len() {
+			1 => Ok(Precision { vertices: Some(decimals[0]), normals: Some(decimals[0]), uvs: Some(decimals[0]) }),
+			3 => Ok(Precision { vertices: Some(decimals[0]), normals: Some(decimals[1]), uvs: Some(decimals[2]) }),
+			_ => Err(())
+		}
+	}
+}
+
+/// Settings of the ASCII model generation.
+pub struct AsciiSettings {
+	/// Export the vertex normals (smooth shading)
+	pub smooth: 	bool,
+	/// Scale applied to the vertices before truncating them to integers.
+	/// `None` writes the vertices without scale and truncate.
+	pub scale: 		Option<f64>,
+	pub precision: 	Precision,
+	/// Bake the material colors into face colors (or vertex colors, multiplied by the ones of the model),
+	/// all the faces using a single material
+	pub bake_colors: bool
+}
+
+/// This function generate the ASCII model (JSON model format 3.1) of the given model.
+/// The bounding box (`null` without finite vertex), bounding sphere and oriented
+/// bounding box of the model are written alongside the geometry, the rotation of the oriented box being
+/// a 3x3 matrix in column-major order (its columns are the axes of the box).
+pub fn generate_ascii_model(model: &Model, materials: &[Material], source_file: &str, settings: &AsciiSettings) -> String {
+
+	let faces = triangulate(&model.faces);
+	
+	let vertices: Vec<String> = model.vertices.iter()
+		.map(|vertex| generate_vertex(vertex, settings.scale, settings.precision.vertices))
+		.collect();
+	
+	let normals: Vec<String> = if settings.smooth {
+		model.normals.iter()
+			.map(|normal| generate_normal(normal, settings.precision.normals))
+			.collect()
+	} else {
+		Vec::new()
+	};
+	
+	let uvs: Vec<String> = model.uvs.iter()
+		.map(|uv| generate_uv(uv, settings.precision.uvs))
+		.collect();
+	
+	// the alpha component can't be written in the ascii format
+	let mut colors: Vec<[f64; 3]> = Vec::new();
+	
+	let faces_string: Vec<String> = if settings.bake_colors {
+		// the colors of the materials, multiplied by the colors of the vertices
+		let mut baked: HashMap<(Option<usize>, usize), usize> = HashMap::new();
+		let mut bake = |vertex: Option<usize>, material: usize| -> usize {
+			*baked.entry((vertex, material)).or_insert_with(|| {
+				let color = material_color(&materials[material], material);
+				let original = vertex.map_or([1.0; 4], |vertex| model.colors[vertex]);
+				colors.push([color[0] * original[0], color[1] * original[1], color[2] * original[2]]);
+				colors.len() - 1
+			})
+		};
+		
+		faces.iter().map(|face| {
+			let material = face.material.min(materials.len() - 1);
+			let indices: Vec<usize> = if model.colors.is_empty() {
+				vec![bake(None, material)]
+			} else {
+				face.vertex.iter().map(|&vertex| bake(Some(vertex), material)).collect()
+			};
+			generate_face(&Face { material: 0, ..face.clone() }, settings.smooth, &indices)
+		}).collect()
+	} else {
+		colors.extend(model.colors.iter().map(|color| [color[0], color[1], color[2]]));
+		
+		faces.iter()
+			.map(|face| generate_face(face, settings.smooth, if model.colors.is_empty() { &[] } else { &face.vertex }))
+			.collect()
+	};
+	
+	let colors: Vec<String> = colors.iter().map(|color| hex_color(color).to_string()).collect();
+	
+	let baked = [baked_material()];
+	let materials = if settings.bake_colors { &baked[..] } else { materials };
+	
+	let bounding_box = match calculate_bounding_box(&model.vertices) {
+		Some(bounding_box) => format!(
+			"{{ \"min\": [{}], \"max\": [{}] }}",
+			generate_vertex(&bounding_box.min, None, settings.precision.vertices),
+			generate_vertex(&bounding_box.max, None, settings.precision.vertices)
+		),
+		None => "null".to_string()
+	};
+	let bounding_sphere = calculate_bounding_sphere(&model.vertices);
+	let oriented_box = calculate_oriented_bounding_box(&model.vertices);
+	let precision = settings.precision.vertices;
+	
+	let pivots: Vec<String> = model.pivots.iter().map(|pivot| {
+		format!(
+			"{{\"name\": {}, \"position\": [{}]}}",
+			json_string(&pivot.name),
+			generate_vertex(&pivot.position, None, settings.precision.vertices)
+		)
+	}).collect();
+	
+	let pivots_string = if pivots.is_empty() {
+		String::new()
+	} else {
+		format!(",\n\n    \"pivots\": [{}]", pivots.join(","))
+	};
+	
+	let materials_string: Vec<String> = materials.iter().enumerate().map(|(index, material)| {
+		let mut properties = material_properties(material, index);
+		
+		// vertex colors are multiplied by the material color, the debug one unless the MTL defines it
+		if settings.bake_colors && model.colors.is_empty() {
+			properties.push(("vertexColors".to_string(), json_string("face")));
+			properties.sort();
+		} else if !model.colors.is_empty() {
+			if material.color_diffuse.is_none() {
+				properties.push(("colorDiffuse".to_string(), json_color(&[1.0, 1.0, 1.0])));
+			}
+			properties.push(("vertexColors".to_string(), "true".to_string()));
+			properties.sort();
+		}
+		
+		let properties: Vec<String> = properties.iter()
+			.map(|(name, value)| format!("\t\"{}\" : {}", name, value))
+			.collect();
+		format!("\t{{\n{}\n\t}}", properties.join(",\n"))
+	}).collect();
+	
+	format!(
+"{{
+
+    \"metadata\" :
+    {{
+        \"formatVersion\" : 3.1,
+        \"sourceFile\"    : {},
+        \"generatedBy\"   : \"OBJConverter\",
+        \"vertices\"      : {},
+        \"faces\"         : {},
+        \"normals\"       : {},
+        \"colors\"        : {},
+        \"uvs\"           : {},
+        \"materials\"     : {}
+    }},
+
+    \"scale\" : {:.6},
+
+    \"boundingBox\" : {},
+
+    \"boundingSphere\" : {{ \"center\": [{}], \"radius\": {} }},
+
+    \"orientedBoundingBox\" : {{ \"center\": [{}], \"halfSize\": [{}], \"rotation\": [{},{},{}] }},
+
+    \"materials\": [{}],
+
+    \"vertices\": [{}],
+
+    \"morphTargets\": [],
+
+    \"morphColors\": [],
+
+    \"normals\": [{}],
+
+    \"colors\": [{}],
+
+    \"uvs\": [[{}]],
+
+    \"faces\": [{}]{}
+
+}}
+",
+		json_string(file_name!(source_file)),
+		vertices.len(),
+		faces.len(),
+		normals.len(),
+		colors.len(),
+		uvs.len(),
+		materials.len(),
+		settings.scale.unwrap_or(1.0),
+		bounding_box,
+		generate_vertex(&bounding_sphere.center, None, precision),
+		format_float(bounding_sphere.radius, precision),
+		generate_vertex(&oriented_box.center, None, precision),
+		generate_vertex(&oriented_box.half_size, None, precision),
+		generate_normal(&oriented_box.axes[0], None),
+		generate_normal(&oriented_box.axes[1], None),
+		generate_normal(&oriented_box.axes[2], None),
+		materials_string.join(",\n\n"),
+		vertices.join(","),
+		normals.join(","),
+		colors.join(","),
+		uvs.join(","),
+		faces_string.join(","),
+		pivots_string
+	)
+	
+}
+
+/// The test module of the converter
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	use std::path::PathBuf;
+
+	#[test]
+	fn test_calculate_bounding_box() {
+	
+		let empty_vertices = [];
+		let vertices = [
+			Vertex { x: 0.0,  y: 0.0, z: 0.0 },
+			Vertex { x: -1.1, y: 1.1, z: 1.1 },
+			Vertex { x: 2.2,  y: 0.0, z: 1.1 },
+			Vertex { x: 1.1,  y: 0.0, z: 2.2 }
+		];
+		let non_finite_vertices = [
+			Vertex { x: f64::NAN, y: 0.0, z: 0.0 },
+			Vertex { x: 1.0,  y: 2.0, z: 3.0 },
+			Vertex { x: 0.0,  y: f64::INFINITY, z: 0.0 },
+			Vertex { x: -1.0, y: 0.0, z: f64::NEG_INFINITY },
+			Vertex { x: 2.0,  y: 1.0, z: 1.0 }
+		];
+		
+		let bounding_box = Box {
+			min: Vertex { x: -1.1, y: 0.0, z: 0.0 },
+			max: Vertex { x: 2.2, y: 1.1, z: 2.2 } 
+		};
+		let non_finite_bounding_box = Box {
+			min: Vertex { x: 1.0, y: 1.0, z: 1.0 },
+			max: Vertex { x: 2.0, y: 2.0, z: 3.0 } 
+		};
+		
+		assert!(Some(bounding_box) 				== calculate_bounding_box(&vertices)			);
+		assert!(calculate_bounding_box(&empty_vertices).is_none());
+		assert!(Some(non_finite_bounding_box) 	== calculate_bounding_box(&non_finite_vertices)	);
+		assert!(calculate_bounding_box(&non_finite_vertices[2..4]).is_none());
+	}
+	
+	#[test]
+	fn test_find_non_finite_vertices() {
+	
+		let vertices = [
+			Vertex { x: 0.0, y: 0.0, z: 0.0 },
+			Vertex { x: f64::NAN, y: 0.0, z: 0.0 },
+			Vertex { x: 1.0, y: 1.0, z: 1.0 },
+			Vertex { x: 0.0, y: 0.0, z: f64::INFINITY }
+		];
+		
+		assert!(find_non_finite_vertices(&vertices) == vec![1, 3]);
+		assert!(find_non_finite_vertices(&vertices[2..3]).is_empty());
+		
+	}
+	
+	#[test]
+	fn test_calculate_part_bounding_boxes() {
+	
+		let obj = "\
+			v 0.0 0.0 0.0\n\
+			v 2.0 0.0 0.0\n\
+			v 2.0 2.0 0.0\n\
+			v 4.0 4.0 4.0\n\
+			o first\n\
+			g one\n\
+			f 1 2 3\n\
+			g two\n\
+			f 2 3 4\n\
+			o second\n\
+			f 4 4 4\n\
+		";
+		
+		let mut model = read_obj(obj.as_bytes());
+		
+		// the faces using a NaN vertex are skipped by read_obj
+		model.vertices[3].x = f64::NAN;
+		
+		let groups = calculate_part_bounding_boxes(&model, PartOption::Group);
+		let objects = calculate_part_bounding_boxes(&model, PartOption::Object);
+		
+		assert!(groups.len() == 2);
+		assert!(groups[0] == ("one".to_string(), Some(Box { min: Vertex { x: 0.0, y: 0.0, z: 0.0 }, max: Vertex { x: 2.0, y: 2.0, z: 0.0 } })));
+		assert!(groups[1] == ("two".to_string(), Some(Box { min: Vertex { x: 2.0, y: 0.0, z: 0.0 }, max: Vertex { x: 2.0, y: 2.0, z: 0.0 } })));
+		assert!(objects.len() == 2);
+		assert!(objects[0].1 == groups[0].1);
+		assert!(objects[1] == ("second".to_string(), None));
+		
+	}
+	
+	#[test]
+	fn test_calculate_bounding_sphere() {
+	
+		let empty_vertices = [];
+		let vertices = [
+			Vertex { x: -1.0, y: 0.0, z: 0.0 },
+			Vertex { x:  1.0, y: 0.0, z: 0.0 },
+			Vertex { x:  0.0, y: 0.5, z: 0.0 },
+			Vertex { x:  0.0, y: 0.0, z: 0.9 }
+		];
+		let cube = [
+			Vertex { x: 0.0, y: 0.0, z: 0.0 },
+			Vertex { x: 2.0, y: 0.0, z: 0.0 },
+			Vertex { x: 0.0, y: 2.0, z: 0.0 },
+			Vertex { x: 0.0, y: 0.0, z: 2.0 },
+			Vertex { x: 2.0, y: 2.0, z: 0.0 },
+			Vertex { x: 2.0, y: 0.0, z: 2.0 },
+			Vertex { x: 0.0, y: 2.0, z: 2.0 },
+			Vertex { x: 2.0, y: 2.0, z: 2.0 }
+		];
+		
+		let empty_sphere = calculate_bounding_sphere(&empty_vertices);
+		let sphere = calculate_bounding_sphere(&vertices);
+		let cube_sphere = calculate_bounding_sphere(&cube);
+		
+		assert!(empty_sphere.center == Vertex { x: 0.0, y: 0.0, z: 0.0 } && empty_sphere.radius == 0.0);
+		assert!(sphere.center == Vertex { x: 0.0, y: 0.0, z: 0.0 } && sphere.radius == 1.0);
+		assert!(
+			fuzzy_cmp(cube_sphere.center.x, 1.0, 0.000001) &&
+			fuzzy_cmp(cube_sphere.center.y, 1.0, 0.000001) &&
+			fuzzy_cmp(cube_sphere.center.z, 1.0, 0.000001) &&
+			fuzzy_cmp(cube_sphere.radius, 3.0f64.sqrt(), 0.000001)
+		);
+		
+		for vertex in cube.iter().chain(vertices.iter()) {
+			let sphere = if vertex.x < 0.0 { sphere } else { cube_sphere };
+			assert!(distance(&sphere.center, vertex) <= sphere.radius + 0.000001);
+		}
+		
+	}
+	
+	#[test]
+	fn test_calculate_oriented_bounding_box() {
+	
+		let empty_vertices = [];
+		let vertices = [
+			Vertex { x: 0.0, y: 0.0,  z: 0.0 },
+			Vertex { x: 4.0, y: 4.0,  z: 0.0 },
+			Vertex { x: 1.0, y: -1.0, z: 0.0 },
+			Vertex { x: 5.0, y: 3.0,  z: 0.0 },
+			Vertex { x: 0.0, y: 0.0,  z: 0.2 },
+			Vertex { x: 4.0, y: 4.0,  z: 0.2 },
+			Vertex { x: 1.0, y: -1.0, z: 0.2 },
+			Vertex { x: 5.0, y: 3.0,  z: 0.2 }
+		];
+		
+		let empty_box = calculate_oriented_bounding_box(&empty_vertices);
+		let oriented_box = calculate_oriented_bounding_box(&vertices);
+		
+		assert!(empty_box.half_size == Vertex { x: 0.0, y: 0.0, z: 0.0 });
+		assert!(empty_box.axes[0] == Vertex { x: 1.0, y: 0.0, z: 0.0 });
+		
+		assert!(
+			fuzzy_cmp(oriented_box.center.x, 2.5, 0.000001) &&
+			fuzzy_cmp(oriented_box.center.y, 1.5, 0.000001) &&
+			fuzzy_cmp(oriented_box.center.z, 0.1, 0.000001)
+		);
+		assert!(
+			fuzzy_cmp(oriented_box.half_size.x, 2.0 * 2.0f64.sqrt(), 0.000001) &&
+			fuzzy_cmp(oriented_box.half_size.y, 0.5 * 2.0f64.sqrt(), 0.000001) &&
+			fuzzy_cmp(oriented_box.half_size.z, 0.1, 0.000001)
+		);
+		assert!(fuzzy_cmp(oriented_box.axes[0].x.abs(), 0.5f64.sqrt(), 0.000001));
+		assert!(fuzzy_cmp(oriented_box.axes[0].y.abs(), 0.5f64.sqrt(), 0.000001));
+		assert!(fuzzy_cmp(oriented_box.axes[2].z.abs(), 1.0, 0.000001));
+		
+		let right_handed = cross(&oriented_box.axes[0], &oriented_box.axes[1]);
+		assert!(fuzzy_cmp(dot(&right_handed, &oriented_box.axes[2]), 1.0, 0.000001));
+		
+	}
+	
+	#[test]
+	fn test_translate() {
+	
+		let mut vertices = [
+			Vertex { x: 0.0,  y: 0.0, z: 0.0 },
+			Vertex { x: -1.1, y: 1.1, z: 1.1 },
+			Vertex { x: 2.2,  y: 0.0, z: 1.1 },
+			Vertex { x: 1.1,  y: 0.0, z: 2.2 }
+		];
+	
+		let translated_vertices = [
+			Vertex { x: 1.0,  y: -2.0, z: 3.3 },
+			Vertex { x: -0.1, y: -0.9, z: 4.4 },
+			Vertex { x: 3.2,  y: -2.0, z: 4.4 },
+			Vertex { x: 2.1,  y: -2.0, z: 5.5 }
+		];
+		
+		let translation_vector = [1.0, -2.0, 3.3];
+		
+		translate(&mut vertices, &translation_vector);
+		
+		for it in vertices.iter_mut().zip(translated_vertices.iter()) {
+			let (vertex, translated_vertex) = it;
+			assert!(
+				fuzzy_cmp(vertex.x, translated_vertex.x, 0.01) &&
+				fuzzy_cmp(vertex.y, translated_vertex.y, 0.01) &&
+				fuzzy_cmp(vertex.z, translated_vertex.z, 0.01)
+			);
+		}
+	}
+	
+	#[test]
+	fn test_center() {
+	
+		let mut vertices = [
+			Vertex { x: 0.0,  y: 0.0, z: 0.0 },
+			Vertex { x: 2.0,  y: 2.0, z: 2.0 },
+			Vertex { x: 4.0,  y: 4.0, z: 4.0 },
+		];
+	
+		let translated_vertices = [
+			Vertex { x: -2.0,  y: -2.0, z: -2.0 },
+			Vertex { x:  0.0,  y:  0.0, z:  0.0 },
+			Vertex { x:  2.0,  y:  2.0, z:  2.0 },
+		];
+		
+		center(&mut vertices);
+		
+		assert!(vertices == translated_vertices);
+		
+	}
+	
+	#[test]
+	fn test_align_top() {
+	
+		let mut vertices = [
+			Vertex { x: 0.0,  y: 0.0, z: 0.0 },
+			Vertex { x: 2.0,  y: 2.0, z: 2.0 },
+			Vertex { x: 4.0,  y: 4.0, z: 4.0 },
+		];
+	
+		let translated_vertices = [
+			Vertex { x: -2.0,  y: -4.0, z: -2.0 },
+			Vertex { x:  0.0,  y: -2.0, z:  0.0 },
+			Vertex { x:  2.0,  y:  0.0, z:  2.0 },
+		];
+		
+		align_top(&mut vertices);
+		
+		assert!(vertices == translated_vertices);
+		
+	}
+	
+	#[test]
+	fn test_align_bottom() {
+	
+		let mut vertices = [
+			Vertex { x: 0.0,  y: -2.0, z: 0.0 },
+			Vertex { x: 2.0,  y:  2.0, z: 2.0 },
+			Vertex { x: 4.0,  y:  4.0, z: 4.0 },
+		];
+	
+		let translated_vertices = [
+			Vertex { x: -2.0,  y: 0.0, z: -2.0 },
+			Vertex { x:  0.0,  y: 4.0, z:  0.0 },
+			Vertex { x:  2.0,  y: 6.0, z:  2.0 },
+		];
+		
+		align_bottom(&mut vertices);
+		
+		assert!(vertices == translated_vertices);
+		
+	}
+	
+	#[test]
+	fn test_center_xz() {
+	
+		let mut vertices = [
+			Vertex { x: 0.0,  y: -2.0, z: 0.0 },
+			Vertex { x: 2.0,  y:  2.0, z: 2.0 },
+			Vertex { x: 4.0,  y:  4.0, z: 4.0 },
+		];
+	
+		let translated_vertices = [
+			Vertex { x: -2.0,  y: -2.0, z: -2.0 },
+			Vertex { x:  0.0,  y:  2.0, z:  0.0 },
+			Vertex { x:  2.0,  y:  4.0, z:  2.0 },
+		];
+		
+		center_xz(&mut vertices);
+		
+		assert!(vertices == translated_vertices);
+		
+	}
+	
+	#[test]
+	fn test_scale() {
+	
+		let mut vertices = [
+			Vertex { x: 0.0,  y: -2.0, z: 0.0 },
+			Vertex { x: 2.0,  y:  2.0, z: 2.0 },
+		];
+	
+		let scaled_vertices = [
+			Vertex { x: 0.0,  y: -1.0, z: 0.0 },
+			Vertex { x: 1.0,  y:  1.0, z: 1.0 },
+		];
+		
+		scale(&mut vertices, 0.5);
+		
+		assert!(vertices == scaled_vertices);
+		
+	}
+	
+	#[test]
+	fn test_fit() {
+	
+		let mut empty_vertices = [];
+		let mut point = [Vertex { x: 1.0,  y: 1.0, z: 1.0 }];
+		let mut vertices = [
+			Vertex { x: 0.0,  y: 0.0, z: 0.0 },
+			Vertex { x: 4.0,  y: 2.0, z: 1.0 },
+		];
+	
+		let scaled_vertices = [
+			Vertex { x: 0.0,  y: 0.0, z: 0.0 },
+			Vertex { x: 2.0,  y: 1.0, z: 0.5 },
+		];
+		
+		assert!(fit(&mut vertices, 2.0) == 0.5);
+		assert!(vertices == scaled_vertices);
+		
+		assert!(fit(&mut empty_vertices, 2.0) == 1.0);
+		assert!(fit(&mut point, 2.0) == 1.0);
+		assert!(point[0] == Vertex { x: 1.0,  y: 1.0, z: 1.0 });
+		
+	}
+	
+	#[test]
+	fn test_fit_unit_cube() {
+	
+		let mut vertices = [
+			Vertex { x: 0.0,  y: 0.0, z: 0.0 },
+			Vertex { x: 4.0,  y: 2.0, z: 2.0 },
+		];
+	
+		let normalized_vertices = [
+			Vertex { x: -0.5,  y: -0.25, z: -0.25 },
+			Vertex { x:  0.5,  y:  0.25, z:  0.25 },
+		];
+		
+		fit_unit_cube(&mut vertices);
+		
+		assert!(vertices == normalized_vertices);
+		
+	}
+	
+	#[test]
+	fn test_align_parts() {
+	
+		let obj = "\
+			v 0.0 0.0 0.0\n\
+			v 2.0 0.0 0.0\n\
+			v 2.0 2.0 0.0\n\
+			v 4.0 4.0 4.0\n\
+			g first\n\
+			f 1 2 3\n\
+			g second\n\
+			f 3 2 4\n\
+			g first\n\
+			f 1 3 2\n\
+		";
+		
+		let mut model = read_obj(obj.as_bytes());
+		
+		align_parts(&mut model, PartOption::Group);
+		
+		assert!(model.vertices.len() == 6);
+		assert!(model.faces[0].vertex == vec![0, 1, 2]);
+		assert!(model.faces[1].vertex == vec![4, 5, 3]);
+		assert!(model.faces[2].vertex == vec![0, 2, 1]);
+		
+		assert!(model.vertices[0] == Vertex { x: -1.0, y: -1.0, z:  0.0 });
+		assert!(model.vertices[2] == Vertex { x:  1.0, y:  1.0, z:  0.0 });
+		assert!(model.vertices[3] == Vertex { x:  1.0, y:  2.0, z:  2.0 });
+		assert!(model.vertices[4] == Vertex { x:  -1.0, y:  0.0, z:  -2.0 });
+		assert!(model.vertices[5] == Vertex { x:  -1.0, y:  -2.0, z:  -2.0 });
+		
+		assert!(model.pivots.len() == 2);
+		assert!(model.pivots[0] == Pivot { name: "first".to_string(), part: PartOption::Group, position: Vertex { x: 1.0, y: 1.0, z: 0.0 } });
+		assert!(model.pivots[1] == Pivot { name: "second".to_string(), part: PartOption::Group, position: Vertex { x: 3.0, y: 2.0, z: 2.0 } });
+		
+	}
+	
+	#[test]
+	fn test_split_model() {
+	
+		let obj = "\
+			v 0.0 0.0 0.0\n\
+			v 2.0 0.0 0.0\n\
+			v 2.0 2.0 0.0\n\
+			f 1 2 3\n\
+			o first\n\
+			g one\n\
+			f 1 2 3\n\
+			o second\n\
+			f 2 3 1\n\
+			o first\n\
+			g two one\n\
+			f 3 1 2\n\
+			o third\n\
+			l 1 2\n\
+			o second\n\
+			p 3\n\
+		";
+		
+		let model = read_obj(obj.as_bytes());
+		
+		let merged = split_model(&model, None);
+		let objects = split_model(&model, Some(PartOption::Object));
+		let groups = split_model(&model, Some(PartOption::Group));
+		
+		assert!(merged.len() == 1);
+		assert!(merged[0].faces == model.faces);
+		assert!(merged[0].lines == model.lines);
+		
+		assert!(objects.len() == 4);
+		assert!(objects[0].name.is_empty() && objects[0].faces.len() == 1);
+		assert!(objects[1].name == "first" && objects[1].faces == vec![model.faces[1].clone(), model.faces[3].clone()]);
+		assert!(objects[2].name == "second" && objects[2].faces == vec![model.faces[2].clone()]);
+		assert!(objects[2].points == model.points);
+		assert!(objects[3].name == "third" && objects[3].faces.is_empty() && objects[3].lines == model.lines);
+		
+		assert!(groups.len() == 3);
+		assert!(groups[1].name == "one" && groups[1].faces.len() == 2);
+		// the face of both groups only goes to the first one
+		assert!(groups[2].name == "two" && groups[2].faces.len() == 1);
+		
+	}
+	
+	#[test]
+	fn test_normalize() {
+		
+		let mut v1 = Vertex { x: 1.0, y: 1.0, z: 1.0 };
+		let mut v2 = Vertex { x: 0.0, y: 0.0, z: 0.0 };
+		let mut v3 = Vertex { x: 2.0, y: 2.0, z: 3.0 };
+		let mut v4 = Vertex { x: -2.0, y: 2.0, z: -3.0 };
+		
+		let v1n = Vertex { x: 0.57735, y: 0.57735, z: 0.57735 };
+		let v2n = Vertex { x: 0.0, y: 0.0, z: 0.0 };
+		let v3n = Vertex { x:  0.485071, y: 0.485071, z:  0.727607 };
+		let v4n = Vertex { x: -0.485071, y: 0.485071, z: -0.727607 };
+		
+		normalize(&mut v1);
+		normalize(&mut v2);
+		normalize(&mut v3);
+		normalize(&mut v4);
+		
+		println!("{} {} {}", v1.x, v1.y, v1.z);
+		println!("{} {} {}", v2.x, v2.y, v2.z);
+		println!("{} {} {}", v3.x, v3.y, v3.z);
+		println!("{} {} {}", v4.x, v4.y, v4.z);		
+		
+		assert!(
+			fuzzy_cmp(v1.x, v1n.x, 0.000001) &&
+			fuzzy_cmp(v1.y, v1n.y, 0.000001) &&
+			fuzzy_cmp(v1.z, v1n.z, 0.000001)
+		);
+		assert!(v2n == v2);
+		assert!(
+			fuzzy_cmp(v3.x, v3n.x, 0.000001) &&
+			fuzzy_cmp(v3.y, v3n.y, 0.000001) &&
+			fuzzy_cmp(v3.z, v3n.z, 0.000001)
+		);
+		assert!(
+			fuzzy_cmp(v4.x, v4n.x, 0.000001) &&
+			fuzzy_cmp(v4.y, v4n.y, 0.000001) &&
+			fuzzy_cmp(v4.z, v4n.z, 0.000001)
+		);
+		
+	}
+	
+	#[test]
+	fn test_read_obj() {
+	
+		let obj = "\
+			# comment\n\
+			mtllib model.mtl\n\
+			v 0.0 0.0 0.0\n\
+			v 1.0 0.0 \\\n\
+			0.0\n\
+			v 1.0 1.0 0.0\n\
+			v 0.0 1.0 0.0\n\
+			vt 0.0 0.0\n\
+			vt 1.0 0.0\n\
+			vn 0.0 0.0 1.0\n\
+			usemtl first\n\
+			f 1/1/1 2/2/1 3/1/1\n\
+			usemtl second\n\
+			f 1//1 3//1 4//1\n\
+			usemtl first\n\
+			f -4 -3 -2 -1\n\
+		";
+		
+		let model = read_obj(obj.as_bytes());
+		
+		assert!(model.mtllib == "model.mtl");
+		assert!(model.vertices.len() == 4);
+		assert!(model.vertices[1] == Vertex { x: 1.0, y: 0.0, z: 0.0 });
+		assert!(model.uvs.len() == 2);
+		assert!(model.normals.len() == 1);
+		assert!(model.materials == vec!["first".to_string(), "second".to_string()]);
+		
+		assert!(model.faces.len() == 3);
+		assert!(model.faces[0].vertex == vec![0, 1, 2]);
+		assert!(model.faces[0].uv == vec![0, 1, 0]);
+		assert!(model.faces[0].normal == vec![0, 0, 0]);
+		assert!(model.faces[1].uv.is_empty());
+		assert!(model.faces[1].material == 1);
+		assert!(model.faces[2].vertex == vec![0, 1, 2, 3]);
+		assert!(model.faces[2].material == 0);
+		
+	}
+	
+	#[test]
+	fn test_read_obj_colors() {
+	
+		let obj = "\
+			v 0.0 0.0 0.0\n\
+			v 1.0 0.0 0.0 1.0 0.0 0.0\n\
+			v 1.0 1.0 0.0 0.0 1.0 0.0 0.5\n\
+			v 0.0 1.0 0.0\n\
+			g first\n\
+			f 1 2 3\n\
+			g second\n\
+			f 1 3 4\n\
+		";
+		
+		let mut model = read_obj(obj.as_bytes());
+		
+		assert!(model.vertices.len() == 4);
+		assert!(model.vertices[2] == Vertex { x: 1.0, y: 1.0, z: 0.0 });
+		assert!(model.colors == vec![[1.0; 4], [1.0, 0.0, 0.0, 1.0], [0.0, 1.0, 0.0, 0.5], [1.0; 4]]);
+		
+		// shared vertices are duplicated with their color
+		align_parts(&mut model, PartOption::Group);
+		
+		assert!(model.vertices.len() == 6);
+		assert!(model.colors.len() == 6);
+		assert!(model.colors[5] == [0.0, 1.0, 0.0, 0.5]);
+		
+		assert!(read_obj("v 0.0 0.0 0.0\nv 1.0 0.0 0.0 0.5\n".as_bytes()).colors.is_empty());
+		
+	}
+	
+	#[test]
+	fn test_read_obj_texture_and_parameter_vertices() {
+	
+		let obj = "\
+			vt 0.5\n\
+			vt 0.5 0.25\n\
+			vt 0.5 0.25 0.125\n\
+			vt 0.5 x\n\
+			vp 0.5\n\
+			vp 0.5 0.25 2.0\n\
+		";
+		
+		let model = read_obj(obj.as_bytes());
+		
+		assert!(model.uvs == vec![
+			Uv { u: 0.5, v: 0.0, w: 0.0 },
+			Uv { u: 0.5, v: 0.25, w: 0.0 },
+			Uv { u: 0.5, v: 0.25, w: 0.125 },
+			// skipped, but kept so the following indices are unchanged
+			Uv { u: 0.0, v: 0.0, w: 0.0 }
+		]);
+		assert!(model.parameter_vertices == vec![
+			Uv { u: 0.5, v: 0.0, w: 1.0 },
+			Uv { u: 0.5, v: 0.25, w: 2.0 }
+		]);
+		
+	}
+	
+	#[test]
+	fn test_substitute_arguments() {
+	
+		let arguments = ["a".to_string(), "b".to_string()];
+		
+		assert!(substitute_arguments("usemtl $1 $2$1 $3 $ $10", &arguments) == "usemtl a ba $3 $ $10");
+		assert!(substitute_arguments("usemtl $1", &[]) == "usemtl $1");
+		
+	}
+	
+	#[test]
+	fn test_expand_obj() {
+	
+		let directory = ::std::env::temp_dir().join(format!("obj2three-call-{}", ::std::process::id()));
+		
+		::std::fs::create_dir_all(directory.join("parts")).unwrap();
+		::std::fs::write(directory.join("parts/part.obj"), "v $1 $2 \\\n0\ncall ../loop.obj $2\ncall missing.obj\n").unwrap();
+		::std::fs::write(directory.join("loop.obj"), "v $1 0 $3\ncall loop.obj\ncall parts/part.obj 7 8\n").unwrap();
+		::std::fs::write(directory.join("model.obj"), "call parts/part.obj 1 2\ncall parts/part.obj 5 6\ncsh -touch never\n").unwrap();
+		
+		let (model, issues) = parse_obj(&directory.join("model.obj").to_string_lossy(), &ParseSettings::default()).unwrap();
+		
+		// calls of a file being read are ignored, and $3 has no argument (the vertices of loop.obj are skipped)
+		assert!(model.vertices.len() == 2);
+		assert!(model.vertices[0] == Vertex { x: 1.0, y: 2.0, z: 0.0 });
+		assert!(model.vertices[1] == Vertex { x: 5.0, y: 6.0, z: 0.0 });
+		assert!(issues.len() == 2);
+		assert!(issues[0].file.ends_with("loop.obj") && issues[0].line == 1 && issues[0].column == 8);
+		// (the position of $3 in the file)
+		assert!(issues[0].message == "$3 isn't a number");
+		
+		let mut stack = Vec::new();
+		let statements = expand_obj("call loop.obj 2 3\n".as_bytes(), &directory.join("model.obj").to_string_lossy(), &[], &ParseSettings::default(), &mut stack);
+		let texts: Vec<&str> = statements.iter().map(|statement| statement.text.as_str()).collect();
+		
+		assert!(texts == vec!["v 2 0 $3", "v 7 8 0"]);
+		assert!(stack.is_empty());
+		
+		// statements keep the file and the line they come from
+		assert!(statements[0].file.ends_with("loop.obj") && statements[0].line == 1);
+		assert!(statements[1].file.ends_with("part.obj") && statements[1].line == 1);
+		
+		// csh is skipped unless allowed, and $1 is replaced by the first argument
+		let statements = expand_obj("csh echo\nv 0 0 $1\n".as_bytes(), "model.obj", &["1".to_string()], &ParseSettings::default(), &mut stack);
+		
+		assert!(statements.len() == 1);
+		assert!(statements[0].text == "v 0 0 1" && statements[0].line == 2);
+		
+		::std::fs::remove_dir_all(&directory).unwrap();
+		
+	}
+	
+	#[test]
+	fn test_read_statements() {
+	
+		let content = "\
+			# comment \\\r\n\
+			v 1\t2 \\\r\n\
+			\t3 # inline comment\r\n\
+			\r\n\
+			\\\n\
+			#MRGB ff000000\n\
+			vt 0 0#1\n\
+			f 1 1 1 \\\
+		";
+		
+		let statements = read_statements(content.as_bytes(), "model.obj");
+		let texts: Vec<&str> = statements.iter().map(|statement| statement.text.as_str()).collect();
+		
+		// a comment ends its line, not the statement
+		assert!(texts == vec!["v 1 2 3", "#MRGB ff000000", "vt 0 0#1", "f 1 1 1"]);
+		assert!(statements[0].file.as_ref() == "model.obj");
+		assert!(statements[0].line == 2);
+		assert!(statements[0].positions == vec![(2, 1), (2, 3), (2, 5), (3, 2), (3, 3)]);
+		assert!(statements[1].line == 6);
+		assert!(statements[3].line == 8 && statements[3].positions[4] == (8, 8));
+		
+		let statements = read_statements("v 0 0 0 # note \\\nf 1 1 1\n".as_bytes(), "model.obj");
+		
+		assert!(statements.len() == 2);
+		assert!(statements[0].text == "v 0 0 0" && statements[1].text == "f 1 1 1");
+		
+		// a Latin-1 comment doesn't end the reading
+		let statements = read_statements(&b"# caf\xe9\nv 0 0 0\nf 1 1 1\n"[..], "model.obj");
+		
+		assert!(statements.len() == 2);
+		assert!(statements[1].text == "f 1 1 1" && statements[1].line == 3);
+		
+	}
+	
+	#[test]
+	fn test_read_obj_checked() {
+	
+		let obj = "\
+			v 0 0 0\r\n\
+			v\t1  0 0\r\n\
+			v nan 1 0\r\n\
+			v 0 1\r\n\
+			v 1 1 0\r\n\
+			vt 0 0\r\n\
+			vn 0 0 x\r\n\
+			f 1/ 2/ 5/\r\n\
+			f 1/1 2/1 3/1\r\n\
+			f 1//1 2//1 5//1\r\n\
+			f 1 2 6\r\n\
+			f 1 2 0\r\n\
+			l 1 2/2\r\n\
+			p -6\r\n\
+			f 1/1//1 2 5\r\n\
+			s 1\r\n\
+			foo bar\r\n\
+			curv 0 1 1 2\r\n\
+			parm u 0 1\r\n\
+			end\r\n\
+			lod high\r\n\
+			f 1 2 5\r\n\
+		";
+		
+		let (model, issues) = read_obj_checked(&read_statements(obj.as_bytes(), "model.obj"), false);
+		
+		// the skipped vertices are removed, the skipped normals keep their index
+		assert!(model.vertices.len() == 3);
+		assert!(find_non_finite_vertices(&model.vertices).is_empty());
+		assert!(model.normals.len() == 1);
+		assert!(model.faces.len() == 2);
+		assert!(model.faces[0].vertex == vec![0, 1, 2] && model.faces[0].uv.is_empty());
+		assert!(model.faces[1].vertex == vec![0, 1, 2]);
+		assert!(model.lines.is_empty() && model.points.is_empty() && model.curves.is_empty());
+		
+		let found: Vec<(usize, usize, IssueKind)> = issues.iter().map(|issue| (issue.line, issue.column, issue.kind)).collect();
+		
+		assert!(found == vec![
+			(3, 3, IssueKind::NonFiniteValue),
+			(4, 6, IssueKind::InvalidValue),
+			(7, 8, IssueKind::InvalidValue),
+			(9, 11, IssueKind::SkippedReference),
+			(10, 3, IssueKind::SkippedReference),
+			(11, 7, IssueKind::IndexOutOfRange),
+			(12, 7, IssueKind::InvalidIndex),
+			(13, 5, IssueKind::IndexOutOfRange),
+			(14, 3, IssueKind::IndexOutOfRange),
+			(15, 3, IssueKind::InvalidIndex),
+			(17, 1, IssueKind::UnknownStatement),
+			(18, 1, IssueKind::MisplacedStatement),
+			(21, 5, IssueKind::InvalidValue)
+		]);
+		assert!(issues[0].to_string() == "model.obj:3:3: nan isn't a finite number");
+		assert!(issues[3].message == "vertex 3 was skipped");
+		assert!(issues[5].message == "vertex index 6 is out of range (5 defined)");
+		
+		assert!(summarize_issues(&issues[..3]) == "3 statements were skipped (1 NaN or infinite value, 2 invalid value)");
+		
+		// the strict mode stops at the first issue
+		let (model, issues) = read_obj_checked(&read_statements(obj.as_bytes(), "model.obj"), true);
+		
+		assert!(issues.len() == 1);
+		assert!(model.vertices.len() == 2);
+		
+	}
+	
+	#[test]
+	fn test_generate_ascii_model_skipped_vertices() {
+	
+		let obj = "\
+			v 0 0 0\n\
+			v 1 x 2\n\
+			v 1 0 0 1 0 0\n\
+			v 0 1 0\n\
+			f 1 3 4\n\
+			f 1 2 3\n\
+			l 3 4\n\
+		";
+		
+		let (model, issues) = read_obj_checked(&read_statements(obj.as_bytes(), "model.obj"), false);
+		
+		assert!(issues.len() == 2);
+		assert!(model.colors.len() == 3 && model.colors[1] == [1.0, 0.0, 0.0, 1.0]);
+		assert!(model.lines[0].vertex == vec![1, 2]);
+		
+		let ascii = generate_ascii_model(&model, &[], "model.obj", &AsciiSettings {
+			smooth: 		false,
+			scale: 			None,
+			precision: 		Precision::default(),
+			bake_colors: 	false
+		});
+		let json: ::serde_json::Value = ::serde_json::from_str(&ascii).unwrap();
+		
+		assert!(json["vertices"] == ::serde_json::json!([0, 0, 0, 1, 0, 0, 0, 1, 0]));
+		assert!(json["faces"] == ::serde_json::json!([130, 0, 1, 2, 0, 0, 1, 2]));
+		
+	}
+	
+	#[test]
+	fn test_read_obj_display() {
+	
+		let obj = "\
+			shadow_obj shadow.obj\n\
+			v 0.0 0.0 0.0\n\
+			v 1.0 0.0 0.0\n\
+			v 1.0 1.0 0.0\n\
+			f 1 2 3\n\
+			lod 150\n\
+			bevel on\n\
+			c_interp on\n\
+			f 1 2 3\n\
+			lod 0\n\
+			bevel off\n\
+			d_interp on\n\
+			l 1 2\n\
+		";
+		
+		let model = read_obj(obj.as_bytes());
+		
+		assert!(model.shadow_obj == "shadow.obj");
+		assert!(model.trace_obj.is_empty());
+		assert!(model.faces[0].display == Display::default());
+		assert!(model.faces[1].display == Display { lod: 100, bevel: true, color_interpolation: true, dissolve_interpolation: false });
+		assert!(model.lines[0].display == Display { lod: 0, bevel: false, color_interpolation: true, dissolve_interpolation: true });
+		assert!(render_directives(&model) == vec!["lod", "bevel", "c_interp", "d_interp", "shadow_obj"]);
+		assert!(render_directives(&Model::default()).is_empty());
+		
+	}
+	
+	#[test]
+	fn test_read_obj_maps() {
+	
+		let obj = "\
+			maplib wood.mpc stone.mpc\n\
+			v 0.0 0.0 0.0\n\
+			v 1.0 0.0 0.0\n\
+			v 1.0 1.0 0.0\n\
+			usemap off\n\
+			f 1 2 3\n\
+			usemap wood\n\
+			f 1 2 3\n\
+			usemtl red\n\
+			f 1 2 3\n\
+			usemap off\n\
+			f 1 2 3\n\
+			usemap wood\n\
+			f 1 2 3\n\
+		";
+		
+		let model = read_obj(obj.as_bytes());
+		
+		assert!(model.maplibs == vec!["wood.mpc".to_string(), "stone.mpc".to_string()]);
+		assert!(model.materials == vec!["".to_string(), "red".to_string(), "red".to_string()]);
+		assert!(model.maps == vec![Some("wood".to_string()), Some("wood".to_string()), None]);
+		assert!(model.faces.iter().map(|face| face.material).collect::<Vec<usize>>() == vec![0, 0, 1, 2, 1]);
+		
+	}
+	
+	#[test]
+	fn test_read_maplib() {
+	
+		let maplib = "\
+			map orphan.png\n\
+			newmap wood\n\
+			map -s 2 2 1 textures\\wood.png\n\
+			newmap stone\n\
+			map_Kd stone.tga\n\
+		";
+		
+		let maps = read_maplib(maplib.as_bytes());
+		
+		assert!(maps.len() == 2);
+		assert!(maps["wood"] == "textures\\wood.png");
+		assert!(maps["stone"] == "stone.tga");
+		
+		// a Latin-1 comment doesn't hide the following maps
+		let maps = read_maplib(&b"# \xe9t\xe9\r\nnewmap wood\r\nmap wood.png\r\n"[..]);
+		
+		assert!(maps["wood"] == "wood.png");
+		
+	}
+	
+	#[test]
+	fn test_read_obj_polypaint() {
+	
+		let obj = "\
+			#MRGB 00ff000000ffffff\n\
+			v 0.0 0.0 0.0\n\
+			v 1.0 0.0 0.0\n\
+			v 1.0 1.0 0.0 0.0 1.0 0.0\n\
+			v 0.0 1.0 0.0 0.0 1.0 0.0\n\
+			#MRGB 000000ff 0000zz00\n\
+			f 1 2 3 4\n\
+		";
+		
+		let model = read_obj(obj.as_bytes());
+		
+		assert!(model.colors == vec![
+			[1.0, 0.0, 0.0, 1.0],
+			[1.0, 1.0, 1.0, 1.0],
+			[0.0, 0.0, 1.0, 1.0],
+			[0.0, 1.0, 0.0, 1.0]
+		]);
+		
+		assert!(read_obj("v 0.0 0.0 0.0\n#MRGB ff\n".as_bytes()).colors == vec![[1.0; 4]]);
+		
+	}
+	
+	#[test]
+	fn test_read_obj_lines_and_points() {
 	
-	if lenght.is_normal() {
-		vertex.x /= lenght;
-		vertex.y /= lenght;
-		vertex.z /= lenght;
+		let obj = "\
+			v 0.0 0.0 0.0\n\
+			v 1.0 0.0 0.0\n\
+			v 1.0 1.0 0.0\n\
+			vt 0.0 0.0\n\
+			o wire\n\
+			l 1 2 3\n\
+			l 1/1 -1/1\n\
+			l 1\n\
+			usemtl dots\n\
+			o cloud\n\
+			p 1 2 3\n\
+		";
+		
+		let model = read_obj(obj.as_bytes());
+		
+		assert!(model.faces.is_empty());
+		assert!(model.lines.len() == 2);
+		assert!(model.lines[0].vertex == vec![0, 1, 2]);
+		assert!(model.lines[0].object == "wire");
+		assert!(model.lines[1].vertex == vec![0, 2]);
+		assert!(model.lines[1].uv == vec![0, 0]);
+		assert!(model.points.len() == 1);
+		assert!(model.points[0].vertex == vec![0, 1, 2]);
+		assert!(model.points[0].material == 0);
+		assert!(model.points[0].object == "cloud");
+		
 	}
-	 
-}
-
-pub fn parse_mtl(file_name: & str) {
-
-	let file = match File::open(file_name) {
-		Ok(file) => file,
-		Err(e) => panic!("Couldn't open {}", file_name)
-	};
-
-	let file = BufReader::new(&file);
-
-	let mut previous_line	:String = String::new();	
-	let mut line			:String;
-
-	for current_line in file.lines() {
 	
-		line = match current_line {
-			Ok(current_line) => format!(
-					"{}{}",
-					previous_line,
-					current_line
-				),
-			Err(_) => previous_line
-		};
+	#[test]
+	fn test_read_obj_free_form() {
+	
+		let obj = "\
+			v 0.0 0.0 0.0\n\
+			v 1.0 1.0 0.0 0.5\n\
+			v 2.0 0.0 0.0\n\
+			vt 0.0 0.0\n\
+			curv 0.0 1.0 1 2 3\n\
+			end\n\
+			cstype rat bspline\n\
+			deg 2\n\
+			ctech cparm 4\n\
+			usemtl wire\n\
+			curv 0.0 1.0 1 2 -1\n\
+			parm u 0.0 0.0 0.0 \\\n\
+			1.0 1.0 1.0\n\
+			end\n\
+			cstype bezier\n\
+			deg 1 1\n\
+			stech cparma 2 3\n\
+			surf 0.0 1.0 0.0 1.0 1/1 2/1 3/1 1/1\n\
+			parm u 0.0 1.0\n\
+			parm v 0.0 1.0\n\
+			trim 0.0 1.0 1\n\
+			end\n\
+		";
 		
-		previous_line = String::new();
+		let model = read_obj(obj.as_bytes());
 		
-		let mut iter = line.rsplitn(2, "\\\\");
+		assert!(model.vertices.len() == 3);
+		assert!(model.curves.len() == 1);
+		
+		let curve = &model.curves[0];
+		
+		assert!(curve.basis == Basis::BSpline);
+		assert!(curve.rational);
+		assert!(curve.degree == [2, 0]);
+		assert!(curve.range == [0.0, 1.0, 0.0, 0.0]);
+		assert!(curve.control == vec![0, 1, 2]);
+		assert!(curve.weights == vec![1.0, 0.5, 1.0]);
+		assert!(curve.parameters[0] == vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0]);
+		assert!(curve.technique == Some(Technique::Parametric(4.0, 4.0)));
+		assert!(curve.material == 0);
+		
+		assert!(model.surfaces.len() == 1);
+		
+		let surface = &model.surfaces[0];
+		
+		assert!(surface.basis == Basis::Bezier);
+		assert!(!surface.rational);
+		assert!(surface.degree == [1, 1]);
+		assert!(surface.control == vec![0, 1, 2, 0]);
+		assert!(surface.uvs == vec![0, 0, 0, 0]);
+		assert!(surface.parameters == [vec![0.0, 1.0], vec![0.0, 1.0]]);
+		assert!(surface.technique == Some(Technique::Parametric(2.0, 3.0)));
+		assert!(surface.trimmed);
 		
-		if iter.next() == Some("") {
-			previous_line = match iter.last() {
-				Some(line) => String::from(line),
-				None => String::new()
-			};
-		} else {
-			let mut chunks = line.splitn(2, ' ');
-			//if chunks.count() > 0 {
-			let first = chunks.next();
-			
-			if first != None {
-				let first = first.unwrap().trim();
-				println!("chunk[0]: {}", first);
-				
-				match first {
-					"newmtl"=> {
-						println!("Let's start a new material!\n");
-					}
-					"map_Kd" => {
-						println!("This is a diffuse map.\n");
-					}
-					_ => println!("Something else:/\n")
-				};
-				
-			}
-
-		}
-
 	}
 	
-}
-
-fn parse_mtl_line(line: & String) {
+	#[test]
+	fn test_read_mtl_checked() {
+	
+		let mtl = "\
+			Kd 0.0 0.0 0.0\n\
+			newmtl red\n\
+			Kd 1.0 0.0\n\
+			Ns inf\n\
+			illum two\n\
+			Tf 1.0 1.0 1.0\n\
+			map_Kd -s 2 2 1 textures/red.png\n\
+			foo\n\
+			d 0.5\n\
+		";
+		
+		let (materials, issues) = read_mtl_checked(&read_statements(mtl.as_bytes(), "model.mtl"), false);
+		
+		assert!(materials.len() == 1);
+		assert!(materials[0].color_diffuse.is_none());
+		assert!(materials[0].specular_coef.is_none());
+		assert!(materials[0].map_diffuse == Some("textures/red.png".to_string()));
+		assert!(materials[0].transparency == Some(0.5));
+		
+		let found: Vec<(usize, usize, IssueKind)> = issues.iter().map(|issue| (issue.line, issue.column, issue.kind)).collect();
+		
+		assert!(found == vec![
+			(1, 1, IssueKind::MisplacedStatement),
+			(3, 11, IssueKind::InvalidValue),
+			(4, 4, IssueKind::NonFiniteValue),
+			(5, 7, IssueKind::InvalidValue),
+			(8, 1, IssueKind::UnknownStatement)
+		]);
+		assert!(issues[1].message == "Kd expects 3 values, found 2");
+		
+		let (materials, issues) = read_mtl_checked(&read_statements(mtl.as_bytes(), "model.mtl"), true);
+		
+		assert!(materials.is_empty() && issues.len() == 1);
+		
+	}
 	
-	let mut chunks = line.splitn(2, ' ');
+	#[test]
+	fn test_read_mtl() {
 	
-	//if chunks.count() > 0 {
-		let first = chunks.next().unwrap();
-		println!("chunk[0]: {}", first);
-	//}
-	/*
-	let chunk_count = chunks.clone().count();
+		let mtl = "\
+			Kd 0.0 0.0 0.0\n\
+			newmtl red # first material\n\
+			Kd 1.0 \\\n\
+			\t0.0 0.0\n\
+			Ns 10\n\
+			# d 0.1\n\
+			d\t0.5\r\n\
+			illum 2\n\
+			map_Kd c:\\textures\\red.png\n\
+			newmtl blue\n\
+			Ka 0.0 0.0 1.0\n\
+			Tr 0.25\n\
+			bump /textures/bump.png\n\
+			newmtl metal\n\
+			Pr 0.25\n\
+			Pm 1.0\n\
+			norm -bm 0.5 textures/normal.png\n\
+			map_Pr textures/roughness.png\n\
+			map_Pm textures/metalness.png\n\
+			newmtl car_paint\n\
+			Pc 1.0\n\
+			Pcr 0.1\n\
+			Ps 0.5\n\
+			aniso 0.3\n\
+			anisor 0.25\n\
+		";
+		
+		let materials = read_mtl(mtl.as_bytes());
+		
+		assert!(materials.len() == 4);
+		assert!(materials[0].name == "red");
+		assert!(materials[0].color_diffuse == Some([1.0, 0.0, 0.0]));
+		assert!(materials[0].specular_coef == Some(10.0));
+		assert!(materials[0].transparency == Some(0.5));
+		assert!(materials[0].illumination == Some(2));
+		assert!(materials[0].map_diffuse == Some("c:\\textures\\red.png".to_string()));
+		assert!(materials[1].name == "blue");
+		assert!(materials[1].color_diffuse.is_none());
+		assert!(materials[1].color_ambient == Some([0.0, 0.0, 1.0]));
+		assert!(materials[1].map_bump == Some("/textures/bump.png".to_string()));
+		assert!(materials[1].transparency_inverse == Some(0.25));
+		assert!(pbr_model(&materials[1]) == MaterialModel::Phong);
+		
+		assert!(materials[2].roughness == Some(0.25));
+		assert!(materials[2].metalness == Some(1.0));
+		assert!(materials[2].map_normal == Some("textures/normal.png".to_string()));
+		assert!(materials[2].map_roughness == Some("textures/roughness.png".to_string()));
+		assert!(materials[2].map_metalness == Some("textures/metalness.png".to_string()));
+		assert!(pbr_model(&materials[2]) == MaterialModel::Standard);
+		
+		assert!(materials[3].clearcoat == Some(1.0));
+		assert!(materials[3].clearcoat_roughness == Some(0.1));
+		assert!(materials[3].sheen == Some(0.5));
+		assert!(materials[3].anisotropy == Some(0.3));
+		assert!(materials[3].anisotropy_rotation == Some(0.25));
+		assert!(pbr_model(&materials[3]) == MaterialModel::Physical);
+		
+	}
 	
-	println!("chunk count: {}", chunk_count);
+	#[test]
+	fn test_precision_from_str() {
 	
-	for chunk in chunks {
-		let mut chunk = chunk;
-		/*if chunks_count > 1 {
-			chunk = chunk.trim();
-		}*/
+		assert!("3".parse::<Precision>() == Ok(Precision { vertices: Some(3), normals: Some(3), uvs: Some(3) }));
+		assert!("4,2,3".parse::<Precision>() == Ok(Precision { vertices: Some(4), normals: Some(2), uvs: Some(3) }));
+		assert!("4,2".parse::<Precision>().is_err());
+		assert!("a".parse::<Precision>().is_err());
 		
-		println!("'{}' ", chunk);
 	}
-	println!("-----\n");*/
-}
-
-/// The test module of the converter
-#[cfg(test)]
-mod tests {
-
-	use super::*;
-	use std::path::PathBuf;
-
+	
 	#[test]
-	fn test_calculate_bounding_box() {
+	fn test_format_float() {
 	
-		let empty_vertices = [];
-		let vertices = [
-			Vertex { x: 0.0,  y: 0.0, z: 0.0 },
-			Vertex { x: -1.1, y: 1.1, z: 1.1 },
-			Vertex { x: 2.2,  y: 0.0, z: 1.1 },
-			Vertex { x: 1.1,  y: 0.0, z: 2.2 }
-		];
+		assert!(format_float(1.0, None) == "1");
+		assert!(format_float(0.0, None) == "0");
+		assert!(format_float(-12.5, None) == "-12.5");
+		assert!(format_float(0.123456789, None) == "0.123457");
+		assert!(format_float(123456.789, None) == "123457");
+		assert!(format_float(0.123456789, Some(2)) == "0.12");
+		assert!(format_float(0.5, Some(0)) == "0");
+		assert!(format_float(1.5, Some(3)) == "1.5");
+		assert!(format_float(-0.0001, Some(2)) == "0");
+		
+	}
 	
-		let empty_bounding_box = Box {
-			min: Vertex { x: 0.0, y: 0.0, z: 0.0 },
-			max: Vertex { x: 0.0, y: 0.0, z: 0.0 }
-		};
-		let bounding_box = Box {
-			min: Vertex { x: -1.1, y: 0.0, z: 0.0 },
-			max: Vertex { x: 2.2, y: 1.1, z: 2.2 } 
-		};
+	#[test]
+	fn test_hex_color() {
+	
+		assert!(hex_color(&[1.0, 1.0, 1.0]) == 0xffffff);
+		assert!(hex_color(&[1.0, 0.5, 0.0]) == 0xff7f00);
+		assert!(hex_color(&[2.0, -1.0, 0.2]) == 0xff0033);
 		
-		assert!(bounding_box 		== calculate_bounding_box(&vertices)		);
-		assert!(empty_bounding_box 	== calculate_bounding_box(&empty_vertices)	);
 	}
 	
 	#[test]
-	fn test_translate() {
+	fn test_generate_vertex() {
 	
-		let mut vertices = [
-			Vertex { x: 0.0,  y: 0.0, z: 0.0 },
-			Vertex { x: -1.1, y: 1.1, z: 1.1 },
-			Vertex { x: 2.2,  y: 0.0, z: 1.1 },
-			Vertex { x: 1.1,  y: 0.0, z: 2.2 }
-		];
+		let vertex = Vertex { x: 0.123, y: -1.55, z: 2.0 };
+		
+		assert!(generate_vertex(&vertex, None, None) == "0.123,-1.55,2");
+		assert!(generate_vertex(&vertex, None, Some(1)) == "0.1,-1.6,2");
+		assert!(generate_vertex(&vertex, Some(10.0), None) == "1,-15,20");
+		assert!(generate_vertex(&vertex, Some(100.0), Some(1)) == "12,-155,200");
+		
+	}
 	
-		let translated_vertices = [
-			Vertex { x: 1.0,  y: -2.0, z: 3.3 },
-			Vertex { x: -0.1, y: -0.9, z: 4.4 },
-			Vertex { x: 3.2,  y: -2.0, z: 4.4 },
-			Vertex { x: 2.1,  y: -2.0, z: 5.5 }
+	#[test]
+	fn test_generate_face() {
+	
+		let triangle = Face { vertex: vec![0, 1, 2], uv: vec![], normal: vec![3, 4, 5], material: 1, ..Face::default() };
+		let quad = Face { vertex: vec![0, 1, 2, 3], uv: vec![4, 5, 6, 7], normal: vec![], material: 0, ..Face::default() };
+		
+		assert!(generate_face(&triangle, true, &[]) == "34,0,1,2,1,3,4,5");
+		assert!(generate_face(&triangle, false, &[]) == "2,0,1,2,1");
+		assert!(generate_face(&quad, true, &[]) == "11,0,1,2,3,0,4,5,6,7");
+		assert!(generate_face(&triangle, true, &triangle.vertex) == "162,0,1,2,1,3,4,5,0,1,2");
+		assert!(generate_face(&quad, false, &quad.vertex) == "139,0,1,2,3,0,4,5,6,7,0,1,2,3");
+		assert!(generate_face(&quad, false, &[2]) == "75,0,1,2,3,0,4,5,6,7,2");
+		
+	}
+	
+	#[test]
+	fn test_triangulate() {
+	
+		let faces = [
+			Face { vertex: vec![0, 1], uv: vec![], normal: vec![], material: 0, ..Face::default() },
+			Face { vertex: vec![0, 1, 2, 3], uv: vec![], normal: vec![], material: 0, ..Face::default() },
+			Face { vertex: vec![0, 1, 2, 3, 4], uv: vec![5, 6, 7, 8, 9], normal: vec![], material: 2, ..Face::default() }
 		];
 		
-		let translation_vector = [1.0, -2.0, 3.3];
+		let triangulated = triangulate(&faces);
 		
-		translate(&mut vertices, &translation_vector);
+		assert!(triangulated.len() == 4);
+		assert!(triangulated[0] == faces[1]);
+		assert!(triangulated[1].vertex == vec![0, 1, 2]);
+		assert!(triangulated[2].vertex == vec![0, 2, 3]);
+		assert!(triangulated[3].vertex == vec![0, 3, 4]);
+		assert!(triangulated[3].uv == vec![5, 8, 9]);
+		assert!(triangulated[3].normal.is_empty());
+		assert!(triangulated[3].material == 2);
 		
-		for it in vertices.iter_mut().zip(translated_vertices.iter()) {
-			let (vertex, translated_vertex) = it;
-			assert!(
-				fuzzy_cmp(vertex.x, translated_vertex.x, 0.01) &&
-				fuzzy_cmp(vertex.y, translated_vertex.y, 0.01) &&
-				fuzzy_cmp(vertex.z, translated_vertex.z, 0.01)
-			);
-		}
 	}
 	
 	#[test]
-	fn test_center() {
+	fn test_material_model() {
 	
-		let mut vertices = [
-			Vertex { x: 0.0,  y: 0.0, z: 0.0 },
-			Vertex { x: 2.0,  y: 2.0, z: 2.0 },
-			Vertex { x: 4.0,  y: 4.0, z: 4.0 },
+		let models = [
+			(Some(0), MaterialModel::Basic),
+			(Some(1), MaterialModel::Lambert),
+			(Some(2), MaterialModel::Phong),
+			(Some(5), MaterialModel::Phong),
+			(Some(7), MaterialModel::Physical),
+			(Some(10), MaterialModel::Shadow),
+			(None, MaterialModel::Phong)
 		];
+		
+		for &(illumination, model) in &models {
+			assert!(material_model(&Material { illumination, ..Material::default() }) == model);
+		}
+		
+		// the PBR statements select the model
+		let material = Material { illumination: Some(1), roughness: Some(0.5), ..Material::default() };
+		
+		assert!(material_model(&material) == MaterialModel::Standard);
 	
-		let translated_vertices = [
-			Vertex { x: -2.0,  y: -2.0, z: -2.0 },
-			Vertex { x:  0.0,  y:  0.0, z:  0.0 },
-			Vertex { x:  2.0,  y:  2.0, z:  2.0 },
+	}
+	
+	#[test]
+	fn test_resolve_transparency() {
+	
+		let mut materials = vec![
+			Material { transparency: Some(0.25), transparency_inverse: Some(0.5), ..Material::default() },
+			Material { transparency_inverse: Some(0.5), ..Material::default() },
+			Material { transparency: Some(1.5), ..Material::default() },
+			Material::default()
 		];
 		
-		center(&mut vertices);
+		let mut inverted = materials.clone();
 		
-		assert!(vertices == translated_vertices);
+		resolve_transparency(&mut materials, false);
+		resolve_transparency(&mut inverted, true);
+		
+		// d takes precedence over Tr, only d being inverted
+		let opacities: Vec<Option<f64>> = materials.iter().map(|material| material.transparency).collect();
+		
+		assert!(opacities == vec![Some(0.25), Some(0.5), Some(1.0), None]);
+		assert!(materials.iter().all(|material| material.transparency_inverse.is_none()));
 		
+		let opacities: Vec<Option<f64>> = inverted.iter().map(|material| material.transparency).collect();
+		
+		assert!(opacities == vec![Some(0.75), Some(0.5), Some(0.0), None]);
+	
 	}
 	
 	#[test]
-	fn test_align_top() {
+	fn test_alpha_mode() {
 	
-		let mut vertices = [
-			Vertex { x: 0.0,  y: 0.0, z: 0.0 },
-			Vertex { x: 2.0,  y: 2.0, z: 2.0 },
-			Vertex { x: 4.0,  y: 4.0, z: 4.0 },
-		];
+		let leaf = Material {
+			map_diffuse: Some("leaf.png".to_string()),
+			map_alpha: Some("leaf_alpha.png".to_string()),
+			..Material::default()
+		};
+		
+		assert!(alpha_mode(&leaf) == AlphaMode::Mask);
+		assert!(alpha_mode(&Material { transparency: Some(1.0), ..leaf.clone() }) == AlphaMode::Mask);
+		assert!(alpha_mode(&Material { transparency: Some(0.5), ..leaf.clone() }) == AlphaMode::Blend);
+		assert!(alpha_mode(&Material { transparency: Some(1.0), ..Material::default() }) == AlphaMode::Opaque);
+		
+		assert!(*alpha_map(&leaf) == Some("leaf_alpha.png".to_string()));
+		assert!(alpha_map(&Material { map_alpha: Some("leaf.png".to_string()), ..leaf }).is_none());
 	
-		let translated_vertices = [
-			Vertex { x: -2.0,  y: -4.0, z: -2.0 },
-			Vertex { x:  0.0,  y: -2.0, z:  0.0 },
-			Vertex { x:  2.0,  y:  0.0, z:  2.0 },
-		];
+	}
+	
+	#[test]
+	fn test_convert_material() {
+	
+		let plastic = Material {
+			color_diffuse: Some([1.0, 0.0, 0.0]),
+			color_specular: Some([0.5, 0.5, 0.5]),
+			specular_coef: Some(225.0),
+			..Material::default()
+		};
 		
-		align_top(&mut vertices);
+		let standard = convert_material(&plastic, MaterialModel::Standard);
 		
-		assert!(vertices == translated_vertices);
+		assert!(standard.roughness == Some(0.5));
+		assert!(standard.metalness == Some(0.0));
+		assert!(standard.color_diffuse == Some([1.0, 0.0, 0.0]));
+		
+		// reflective illumination models use the strength of the specular color
+		let mirror = convert_material(&Material { illumination: Some(3), ..plastic.clone() }, MaterialModel::Physical);
+		
+		assert!(mirror.metalness == Some(0.5));
+		assert!(mirror.color_diffuse == Some([0.75, 0.25, 0.25]));
+		
+		let mirror = convert_material(&Material { illumination: Some(3), ..plastic.clone() }, MaterialModel::Lambert);
+		
+		assert!(mirror.reflectivity == Some(0.5));
+		assert!(mirror.metalness.is_none());
+		
+		// transparent illumination models transmit the light
+		let glass = convert_material(&Material { illumination: Some(6), transparency: Some(0.5), ..plastic.clone() }, MaterialModel::Physical);
+		
+		assert!(glass.transmission == Some(0.5));
+		assert!(convert_material(&Material { illumination: Some(6), ..plastic.clone() }, MaterialModel::Physical).transmission == Some(1.0));
+		assert!(convert_material(&Material { illumination: Some(6), ..plastic.clone() }, MaterialModel::Standard).transmission.is_none());
+		
+		// tinted highlights are metallic
+		let gold = convert_material(&Material {
+			color_diffuse: Some([0.2, 0.2, 0.2]),
+			color_specular: Some([1.0, 0.8, 0.2]),
+			..Material::default()
+		}, MaterialModel::Standard);
+		
+		assert!(gold.metalness == Some(1.0));
+		assert!(gold.color_diffuse == Some([1.0, 0.8, 0.2]));
+		assert!(gold.roughness.is_none());
+		
+		// the values of the MTL file are kept, and textures aren't tinted
+		let textured = convert_material(&Material {
+			color_specular: Some([1.0, 0.8, 0.2]),
+			metalness: Some(0.25),
+			map_diffuse: Some("wood.png".to_string()),
+			..Material::default()
+		}, MaterialModel::Standard);
+		
+		assert!(textured.metalness == Some(0.25));
+		assert!(textured.color_diffuse == Some([1.0, 1.0, 1.0]));
+		
+		let phong = convert_material(&Material { roughness: Some(0.5), clearcoat: Some(1.0), ..Material::default() }, MaterialModel::Phong);
+		
+		assert!(phong.specular_coef == Some(225.0));
+		assert!(pbr_model(&phong) == MaterialModel::Phong);
 		
 	}
 	
 	#[test]
-	fn test_align_bottom() {
+	fn test_create_materials() {
 	
-		let mut vertices = [
-			Vertex { x: 0.0,  y: -2.0, z: 0.0 },
-			Vertex { x: 2.0,  y:  2.0, z: 2.0 },
-			Vertex { x: 4.0,  y:  4.0, z: 4.0 },
-		];
-	
-		let translated_vertices = [
-			Vertex { x: -2.0,  y: 0.0, z: -2.0 },
-			Vertex { x:  0.0,  y: 4.0, z:  0.0 },
-			Vertex { x:  2.0,  y: 6.0, z:  2.0 },
-		];
+		let mut model = Model::default();
+		let mtl = [Material { name: "red".to_string(), color_diffuse: Some([1.0, 0.0, 0.0]), ..Material::default() }];
 		
-		align_bottom(&mut vertices);
+		let mut maps = HashMap::new();
 		
-		assert!(vertices == translated_vertices);
+		let materials = create_materials(&model, &mtl, &maps);
+		
+		assert!(materials.len() == 1);
+		assert!(materials[0].name == "default");
+		
+		model.materials = vec!["unknown".to_string(), "red".to_string()];
+		
+		let materials = create_materials(&model, &mtl, &maps);
+		
+		assert!(materials.len() == 2);
+		assert!(materials[0] == Material { name: "unknown".to_string(), ..Material::default() });
+		assert!(materials[1] == mtl[0]);
+		
+		maps.insert("wood".to_string(), "wood.png".to_string());
+		model.materials = vec!["red".to_string(), "red".to_string(), "red".to_string()];
+		model.maps = vec![None, Some("wood".to_string()), Some("stone.png".to_string())];
+		
+		let materials = create_materials(&model, &mtl, &maps);
+		
+		assert!(materials[0] == mtl[0]);
+		assert!(materials[1].name == "red_wood");
+		assert!(materials[1].color_diffuse == Some([1.0, 0.0, 0.0]));
+		assert!(materials[1].map_diffuse == Some("wood.png".to_string()));
+		assert!(materials[2].map_diffuse == Some("stone.png".to_string()));
 		
 	}
 	
 	#[test]
-	fn test_center_xz() {
+	fn test_bake_vertex_colors() {
 	
-		let mut vertices = [
-			Vertex { x: 0.0,  y: -2.0, z: 0.0 },
-			Vertex { x: 2.0,  y:  2.0, z: 2.0 },
-			Vertex { x: 4.0,  y:  4.0, z: 4.0 },
+		let mut model = read_obj("\
+			v 0 0 0 0.5 0.5 0.5\n\
+			v 1 0 0\n\
+			v 1 1 0\n\
+			v 0 1 0\n\
+			usemtl red\n\
+			f 1 2 3\n\
+			usemtl blue\n\
+			f 1 3 4\n\
+			l 1 4\n\
+			usemtl unknown\n\
+			p 1\n\
+		".as_bytes());
+		
+		let materials = [
+			Material { color_diffuse: Some([1.0, 0.0, 0.0]), ..Material::default() },
+			Material { color_diffuse: Some([0.0, 0.0, 1.0]), ..Material::default() },
+			Material::default()
 		];
+		
+		let baked = bake_vertex_colors(&mut model, &materials);
+		
+		assert!(baked == vec![baked_material()]);
+		assert!(model.materials == vec!["baked".to_string()]);
+		assert!(model.faces.iter().chain(&model.lines).chain(&model.points).all(|element| element.material == 0));
+		
+		// the vertices shared by several materials are duplicated once per material
+		assert!(model.vertices.len() == 7);
+		assert!(model.faces[0].vertex == vec![0, 1, 2]);
+		assert!(model.faces[1].vertex == vec![4, 5, 3]);
+		assert!(model.lines[0].vertex == vec![4, 3]);
+		assert!(model.points[0].vertex == vec![6]);
+		assert!(model.vertices[4] == model.vertices[0]);
+		
+		assert!(model.colors == vec![
+			[0.5, 0.0, 0.0, 1.0],
+			[1.0, 0.0, 0.0, 1.0],
+			[1.0, 0.0, 0.0, 1.0],
+			[0.0, 0.0, 1.0, 1.0],
+			[0.0, 0.0, 0.5, 1.0],
+			[0.0, 0.0, 1.0, 1.0],
+			[0.0, 0.5 * 238.0 / 255.0, 0.0, 1.0]
+		]);
 	
-		let translated_vertices = [
-			Vertex { x: -2.0,  y: -2.0, z: -2.0 },
-			Vertex { x:  0.0,  y:  2.0, z:  0.0 },
-			Vertex { x:  2.0,  y:  4.0, z:  2.0 },
+	}
+	
+	#[test]
+	fn test_generate_ascii_model_baked_colors() {
+	
+		let model = read_obj("v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nusemtl red\nf 1 2 3\nusemtl blue\nf 1 3 4\n".as_bytes());
+		let materials = [
+			Material { name: "red".to_string(), color_diffuse: Some([1.0, 0.0, 0.0]), ..Material::default() },
+			Material { name: "blue".to_string(), color_diffuse: Some([0.0, 0.0, 1.0]), ..Material::default() }
 		];
 		
-		center_xz(&mut vertices);
-		
-		assert!(vertices == translated_vertices);
+		let ascii = generate_ascii_model(&model, &materials, "model.obj", &AsciiSettings {
+			smooth: 		false,
+			scale: 			None,
+			precision: 		Precision::default(),
+			bake_colors: 	true
+		});
 		
+		assert!(ascii.contains("\"colors\": [16711680,255]"));
+		assert!(ascii.contains("\"faces\": [66,0,1,2,0,0,66,0,2,3,0,1]"));
+		assert!(ascii.contains("\"vertexColors\" : \"face\""));
+		assert!(ascii.contains("\"materials\"     : 1"));
+		assert!(!ascii.contains("\"red\""));
+	
 	}
 	
 	#[test]
-	fn test_normalize() {
+	fn test_material_properties() {
+	
+		let material = Material {
+			name: "a \"red\" one".to_string(),
+			color_diffuse: Some([1.0, 0.0, 0.5]),
+			map_diffuse: Some("red.png".to_string()),
+			transparency: Some(0.5),
+			..Material::default()
+		};
 		
-		let mut v1 = Vertex { x: 1.0, y: 1.0, z: 1.0 };
-		let mut v2 = Vertex { x: 0.0, y: 0.0, z: 0.0 };
-		let mut v3 = Vertex { x: 2.0, y: 2.0, z: 3.0 };
-		let mut v4 = Vertex { x: -2.0, y: 2.0, z: -3.0 };
+		let properties = material_properties(&material, 8);
+		let names: Vec<&str> = properties.iter().map(|(name, _)| name.as_str()).collect();
 		
-		let v1n = Vertex { x: 0.57735, y: 0.57735, z: 0.57735 };
-		let v2n = Vertex { x: 0.0, y: 0.0, z: 0.0 };
-		let v3n = Vertex { x:  0.485071, y: 0.485071, z:  0.727607 };
-		let v4n = Vertex { x: -0.485071, y: 0.485071, z: -0.727607 };
+		assert!(names == vec!["DbgColor", "DbgIndex", "DbgName", "colorDiffuse", "depthWrite", "mapDiffuse", "transparency", "transparent"]);
+		assert!(properties[0].1 == "15597568");
+		assert!(properties[1].1 == "8");
+		assert!(properties[2].1 == "\"a \\\"red\\\" one\"");
+		assert!(properties[3].1 == "[1, 0, 0.5]");
+		assert!(properties[4].1 == "false");
+		assert!(properties[5].1 == "\"red.png\"");
 		
-		normalize(&mut v1);
-		normalize(&mut v2);
-		normalize(&mut v3);
-		normalize(&mut v4);
+		// an opaque dissolve isn't transparent, and the alpha channel of the diffuse texture is used
+		let material = Material { transparency: Some(1.0), map_alpha: Some("red.png".to_string()), ..material };
+		let names: Vec<String> = material_properties(&material, 8).into_iter().map(|(name, _)| name).collect();
 		
-		println!("{} {} {}", v1.x, v1.y, v1.z);
-		println!("{} {} {}", v2.x, v2.y, v2.z);
-		println!("{} {} {}", v3.x, v3.y, v3.z);
-		println!("{} {} {}", v4.x, v4.y, v4.z);		
+		assert!(names == vec!["DbgColor", "DbgIndex", "DbgName", "colorDiffuse", "mapDiffuse", "transparency", "transparent"]);
 		
-		assert!(
-			fuzzy_cmp(v1.x, v1n.x, 0.000001) &&
-			fuzzy_cmp(v1.y, v1n.y, 0.000001) &&
-			fuzzy_cmp(v1.z, v1n.z, 0.000001)
-		);
-		assert!(v2n == v2);
-		assert!(
-			fuzzy_cmp(v3.x, v3n.x, 0.000001) &&
-			fuzzy_cmp(v3.y, v3n.y, 0.000001) &&
-			fuzzy_cmp(v3.z, v3n.z, 0.000001)
-		);
-		assert!(
-			fuzzy_cmp(v4.x, v4n.x, 0.000001) &&
-			fuzzy_cmp(v4.y, v4n.y, 0.000001) &&
-			fuzzy_cmp(v4.z, v4n.z, 0.000001)
-		);
+		let material = Material { illumination: Some(0), ..Material::default() };
+		
+		assert!(material_properties(&material, 0).contains(&("shading".to_string(), "\"basic\"".to_string())));
+		
+		let material = convert_material(&Material { illumination: Some(3), ..Material::default() }, MaterialModel::Phong);
+		let properties = material_properties(&material, 0);
+		
+		assert!(properties.contains(&("shading".to_string(), "\"phong\"".to_string())));
+		assert!(properties.contains(&("reflectivity".to_string(), "1".to_string())));
 		
 	}
 	
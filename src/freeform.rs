@@ -0,0 +1,726 @@
+//! Free-form curves and surfaces tessellation
+//!
+//! Curves (`curv`) are approximated by polylines added to the lines of the model,
+//! and surfaces (`surf`) by a grid of triangles added to its faces, so they are
+//! exported like any other line or face.
+
+use std::mem;
+
+use converter::{
+	Model, FreeForm, Basis, Technique, Face, Vertex, Uv,
+	distance, dot, cross, normalize
+};
+
+/// Number of samples used to measure a span with the `cspace` and `curv` techniques
+const SAMPLES: usize = 16;
+
+/// Maximum number of segments of one span
+const MAX_SEGMENTS: usize = 256;
+
+/// This function return the number of control points a curve or surface needs in one direction,
+/// given its basis, its degree and its parameters (`parm`) in this direction.
+/// `None` if the parameters don't define a curve (or if the basis isn't supported).
+///
+/// # Examples
+///
+/// ```
+/// assert!(control_count(Basis::Bezier, 3, &[0.0, 1.0, 2.0]) == Some(7));
+/// assert!(control_count(Basis::BSpline, 2, &[0.0, 0.0, 0.0, 1.0, 1.0, 1.0]) == Some(3));
+/// assert!(control_count(Basis::BMatrix, 3, &[0.0, 1.0]).is_none());
+/// ```
+pub fn control_count(basis: Basis, degree: usize, parameters: &[f64]) -> Option<usize> {
+
+	let count = parameters.len();
+	
+	match basis {
+		Basis::Bezier if degree >= 1 && count >= 2 => Some(degree * (count - 1) + 1),
+		Basis::BSpline if degree >= 1 && count >= 2 * degree + 2 => Some(count - degree - 1),
+		Basis::Cardinal if degree == 3 && count >= 2 => Some(count + 2),
+		Basis::Taylor if count >= 2 => Some((degree + 1) * (count - 1)),
+		_ => None
+	}
+	
+}
+
+/// The parameters where the polynomial spans of a curve start and end
+fn breakpoints(basis: Basis, degree: usize, parameters: &[f64]) -> &[f64] {
+	match basis {
+		Basis::BSpline => &parameters[degree..parameters.len() - degree],
+		_ => parameters
+	}
+}
+
+/// Index of the span of `breakpoints` containing `t`, and the position of `t` in this span
+fn span(breakpoints: &[f64], t: f64) -> (usize, f64) {
+
+	let last = breakpoints.len() - 2;
+	let index = (0..last).find(|&index| t < breakpoints[index + 1]).unwrap_or(last);
+	let (start, end) = (breakpoints[index], breakpoints[index + 1]);
+	
+	(index, if end != start { (t - start) / (end - start) } else { 0.0 })
+	
+}
+
+/// Bernstein polynomial `i` of the given degree
+fn bernstein(degree: usize, i: usize, u: f64) -> f64 {
+
+	let binomial = (0..i).fold(1.0, |binomial, k| binomial * (degree - k) as f64 / (k + 1) as f64);
+	
+	binomial * u.powi(i as i32) * (1.0 - u).powi((degree - i) as i32)
+	
+}
+
+/// B-spline basis functions which are not zero at `t` (Cox-de Boor recursion)
+fn bspline(degree: usize, knots: &[f64], t: f64) -> Vec<(usize, f64)> {
+
+	let count = knots.len() - degree - 1;
+	let mut span = degree;
+	
+	while span + 1 < count && t >= knots[span + 1] {
+		span += 1;
+	}
+	
+	let mut values = vec![0.0; degree + 1];
+	let mut left = vec![0.0; degree + 1];
+	let mut right = vec![0.0; degree + 1];
+	
+	values[0] = 1.0;
+	
+	for j in 1..degree + 1 {
+		left[j] = t - knots[span + 1 - j];
+		right[j] = knots[span + j] - t;
+		
+		let mut saved = 0.0;
+		
+		for r in 0..j {
+			let denominator = right[r + 1] + left[j - r];
+			let temp = if denominator != 0.0 { values[r] / denominator } else { 0.0 };
+			
+			values[r] = saved + right[r + 1] * temp;
+			saved = left[j - r] * temp;
+		}
+		
+		values[j] = saved;
+	}
+	
+	values.into_iter().enumerate().map(|(i, value)| (span - degree + i, value)).collect()
+	
+}
+
+/// This function evaluate the basis functions of a curve at the parameter `t`,
+/// returning the index of each control point involved with its coefficient.
+///
+/// # Examples
+///
+/// ```
+/// let basis = basis_functions(Basis::Bezier, 1, &[0.0, 1.0, 2.0], 1.5);
+///
+/// assert!(basis == vec![(1, 0.5), (2, 0.5)]);
+/// ```
+pub fn basis_functions(basis: Basis, degree: usize, parameters: &[f64], t: f64) -> Vec<(usize, f64)> {
+
+	match basis {
+		Basis::Bezier => {
+			let (index, u) = span(parameters, t);
+			
+			(0..degree + 1).map(|i| (index * degree + i, bernstein(degree, i, u))).collect()
+		}
+		Basis::BSpline => bspline(degree, parameters, t),
+		// Catmull-Rom spline, the segment of a span going from its second to its third control point
+		Basis::Cardinal => {
+			let (index, u) = span(parameters, t);
+			let (u2, u3) = (u * u, u * u * u);
+			
+			vec![
+				(index, -0.5 * u3 + u2 - 0.5 * u),
+				(index + 1, 1.5 * u3 - 2.5 * u2 + 1.0),
+				(index + 2, -1.5 * u3 + 2.0 * u2 + 0.5 * u),
+				(index + 3, 0.5 * u3 - 0.5 * u2)
+			]
+		}
+		// control points are the coefficients of the polynomial of each span
+		Basis::Taylor => {
+			let (index, _) = span(parameters, t);
+			let u = t - parameters[index];
+			
+			(0..degree + 1).map(|i| (index * (degree + 1) + i, u.powi(i as i32))).collect()
+		}
+		Basis::BMatrix => Vec::new()
+	}
+	
+}
+
+/// Number of control points in u and v of a curve or surface, `None` if it can't be evaluated
+fn control_counts(model: &Model, element: &FreeForm, surface: bool) -> Option<[usize; 2]> {
+
+	let u = control_count(element.basis, element.degree[0], &element.parameters[0])?;
+	let v = if surface { control_count(element.basis, element.degree[1], &element.parameters[1])? } else { 1 };
+	
+	let valid =
+		element.control.len() == u * v &&
+		element.control.iter().all(|&index| index < model.vertices.len()) &&
+		(element.uvs.is_empty() || element.uvs.len() == u * v) &&
+		element.uvs.iter().all(|&index| index < model.uvs.len());
+		
+	if valid { Some([u, v]) } else { None }
+	
+}
+
+/// A point of a curve or surface, with its uv when the control points have
+/// texture vertices, and its color when the model has vertex colors.
+type Point = (Vertex, Option<Uv>, Option<[f64; 4]>);
+
+/// Point of a curve or surface at the parameters `t` (u, v).
+fn evaluate(model: &Model, element: &FreeForm, counts: [usize; 2], surface: bool, t: [f64; 2]) -> Point {
+	
+	let basis_u = basis_functions(element.basis, element.degree[0], &element.parameters[0], t[0]);
+	let basis_v = if surface {
+		basis_functions(element.basis, element.degree[1], &element.parameters[1], t[1])
+	} else {
+		vec![(0, 1.0)]
+	};
+	
+	let mut point = Vertex { x: 0.0, y: 0.0, z: 0.0 };
+	let mut uv = Uv { u: 0.0, v: 0.0, w: 0.0 };
+	let mut color = [0.0; 4];
+	let mut total = 0.0;
+	
+	for &(j, value_v) in &basis_v {
+		for &(i, value_u) in &basis_u {
+			let index = i + j * counts[0];
+			let weight = if element.rational { element.weights[index] } else { 1.0 } * value_u * value_v;
+			let vertex = &model.vertices[element.control[index]];
+			
+			point.x += vertex.x * weight;
+			point.y += vertex.y * weight;
+			point.z += vertex.z * weight;
+			
+			if !element.uvs.is_empty() {
+				uv.u += model.uvs[element.uvs[index]].u * weight;
+				uv.v += model.uvs[element.uvs[index]].v * weight;
+				uv.w += model.uvs[element.uvs[index]].w * weight;
+			}
+			if !model.colors.is_empty() {
+				for (component, value) in color.iter_mut().zip(&model.colors[element.control[index]]) {
+					*component += value * weight;
+				}
+			}
+			
+			total += weight;
+		}
+	}
+	
+	if element.rational && total != 0.0 {
+		point = Vertex { x: point.x / total, y: point.y / total, z: point.z / total };
+		uv = Uv { u: uv.u / total, v: uv.v / total, w: uv.w / total };
+		color = [color[0] / total, color[1] / total, color[2] / total, color[3] / total];
+	}
+	
+	(
+		point,
+		if element.uvs.is_empty() { None } else { Some(uv) },
+		if model.colors.is_empty() { None } else { Some(color) }
+	)
+	
+}
+
+/// Vector going from `a` to `b`
+fn vector(a: &Vertex, b: &Vertex) -> Vertex {
+	Vertex { x: b.x - a.x, y: b.y - a.y, z: b.z - a.z }
+}
+
+/// This function return the number of segments of the span going from `start` to `end`,
+/// `resolution` when there is no approximation technique, otherwise following the technique
+/// in the given direction (0 for u, 1 for v), `curve` giving the points of the span.
+///
+/// # Examples
+///
+/// ```
+/// let line = |t: f64| Vertex { x: t, y: 0.0, z: 0.0 };
+///
+/// assert!(segments(None, 0, 8, &line, 0.0, 1.0) == 8);
+/// assert!(segments(Some(Technique::Parametric(2.0, 3.0)), 1, 8, &line, 0.0, 1.0) == 3);
+/// assert!(segments(Some(Technique::Space(0.25)), 0, 8, &line, 0.0, 1.0) == 4);
+/// ```
+pub fn segments<F: Fn(f64) -> Vertex>(technique: Option<Technique>, direction: usize, resolution: usize, curve: &F, start: f64, end: f64) -> usize {
+
+	let points: Vec<Vertex> = (0..SAMPLES + 1)
+		.map(|i| curve(start + (end - start) * i as f64 / SAMPLES as f64))
+		.collect();
+	let length: f64 = points.windows(2).map(|pair| distance(&pair[0], &pair[1])).sum();
+	
+	let segments = match technique {
+		None => resolution as f64,
+		Some(Technique::Parametric(u, v)) => if direction == 0 { u } else { v },
+		Some(Technique::Space(max_length)) => length / max_length,
+		Some(Technique::Curvature(max_distance, max_angle)) => {
+			// total turning angle of the span
+			let angle: f64 = points.windows(3).map(|triple| {
+				let (a, b) = (vector(&triple[0], &triple[1]), vector(&triple[1], &triple[2]));
+				let lengths = (dot(&a, &a) * dot(&b, &b)).sqrt();
+				
+				if lengths > 0.0 { (dot(&a, &b) / lengths).clamp(-1.0, 1.0).acos() } else { 0.0 }
+			}).sum();
+			
+			// an arc of this angle and length, divided so its chords stay close enough
+			let radius = length / angle;
+			let by_distance = if angle > 0.0 && max_distance < radius {
+				angle / (2.0 * (1.0 - max_distance / radius).acos())
+			} else {
+				1.0
+			};
+			
+			by_distance.max(angle / max_angle.to_radians())
+		}
+	};
+	
+	// NaN (and negative) values become 0
+	(segments.ceil() as usize).clamp(1, MAX_SEGMENTS)
+	
+}
+
+/// Parameters where a curve is sampled between `start` and `end`: the breakpoints in this
+/// range, each span being divided in the number of segments returned by `segments`.
+fn samples<F: Fn(f64, f64) -> usize>(breakpoints: &[f64], start: f64, end: f64, segments: F) -> Vec<f64> {
+
+	let (low, high) = (start.min(end), start.max(end));
+	
+	let mut inner: Vec<f64> = breakpoints.iter().cloned().filter(|&t| t > low && t < high).collect();
+	
+	inner.dedup();
+	
+	if start > end {
+		inner.reverse();
+	}
+	
+	let mut limits = vec![start];
+	
+	limits.extend(inner);
+	limits.push(end);
+	
+	let mut values = vec![start];
+	
+	for limit in limits.windows(2) {
+		let count = segments(limit[0], limit[1]);
+		
+		values.extend((1..count + 1).map(|i| limit[0] + (limit[1] - limit[0]) * i as f64 / count as f64));
+	}
+	
+	values
+	
+}
+
+/// This function tessellate the free-form curves and surfaces of the model: each curve
+/// becomes a line, and each surface a grid of triangles with computed normals
+/// (and uvs when its control points have texture vertices). Vertex colors
+/// are interpolated like the positions.
+/// Spans are divided in `resolution` segments, unless the curve or surface has
+/// an approximation technique (`ctech` / `stech`).
+/// Trimming curves and holes are not supported: the trimmed surfaces (`trim` or `hole`) are left out
+/// and reported, rather than drawn untrimmed. Elements which can't be evaluated
+/// (`bmatrix` basis, wrong number of control points or parameters) are ignored.
+///
+/// # Examples
+///
+/// ```
+/// let mut model = read_obj("\
+///     v 0 0 0\n\
+///     v 1 1 0\n\
+///     v 2 0 0\n\
+///     cstype bezier\n\
+///     deg 2\n\
+///     curv 0 1 1 2 3\n\
+///     parm u 0 1\n\
+///     end\n\
+/// ".as_bytes());
+///
+/// tessellate(&mut model, 8);
+///
+/// assert!(model.curves.is_empty());
+/// assert!(model.lines[0].vertex.len() == 9);
+/// ```
+pub fn tessellate(model: &mut Model, resolution: usize) {
+
+	let curves = mem::take(&mut model.curves);
+	let surfaces = mem::take(&mut model.surfaces);
+	
+	let mut ignored = 0;
+	let mut trimmed = 0;
+	
+	for curve in &curves {
+	
+		let counts = match control_counts(model, curve, false) {
+			Some(counts) => counts,
+			None => {
+				ignored += 1;
+				continue;
+			}
+		};
+		
+		let points: Vec<Point> = {
+			let position = |t: f64| evaluate(model, curve, counts, false, [t, 0.0]).0;
+			let breaks = breakpoints(curve.basis, curve.degree[0], &curve.parameters[0]);
+			
+			samples(breaks, curve.range[0], curve.range[1], |start, end| segments(curve.technique, 0, resolution, &position, start, end))
+				.into_iter()
+				.map(|t| evaluate(model, curve, counts, false, [t, 0.0]))
+				.collect()
+		};
+		
+		let first = model.vertices.len();
+		
+		model.vertices.extend(points.iter().map(|point| point.0));
+		model.colors.extend(points.iter().filter_map(|point| point.2));
+		model.lines.push(Face {
+			vertex: 	(first..model.vertices.len()).collect(),
+			material: 	curve.material,
+			group: 		curve.group.clone(),
+			object: 	curve.object.clone(),
+			display: 	curve.display,
+			..Face::default()
+		});
+		
+	}
+	
+	for surface in &surfaces {
+	
+		// without trimming, the holes would be filled and the trimmed-away areas drawn
+		if surface.trimmed {
+			trimmed += 1;
+			continue;
+		}
+		
+		let counts = match control_counts(model, surface, true) {
+			Some(counts) => counts,
+			None => {
+				ignored += 1;
+				continue;
+			}
+		};
+		
+		let (points, uvs, colors, us) = {
+			let range = surface.range;
+			let middle = [(range[0] + range[1]) / 2.0, (range[2] + range[3]) / 2.0];
+			
+			let iso_u = |t: f64| evaluate(model, surface, counts, true, [t, middle[1]]).0;
+			let iso_v = |t: f64| evaluate(model, surface, counts, true, [middle[0], t]).0;
+			
+			let us = samples(
+				breakpoints(surface.basis, surface.degree[0], &surface.parameters[0]), range[0], range[1],
+				|start, end| segments(surface.technique, 0, resolution, &iso_u, start, end)
+			);
+			let vs = samples(
+				breakpoints(surface.basis, surface.degree[1], &surface.parameters[1]), range[2], range[3],
+				|start, end| segments(surface.technique, 1, resolution, &iso_v, start, end)
+			);
+			
+			let grid: Vec<Point> = vs.iter()
+				.flat_map(|&v| us.iter().map(move |&u| [u, v]))
+				.map(|t| evaluate(model, surface, counts, true, t))
+				.collect();
+				
+			let points: Vec<Vertex> = grid.iter().map(|point| point.0).collect();
+			let uvs: Vec<Uv> = grid.iter().filter_map(|point| point.1).collect();
+			let colors: Vec<[f64; 4]> = grid.iter().filter_map(|point| point.2).collect();
+			
+			(points, uvs, colors, us.len())
+		};
+		
+		let first_vertex = model.vertices.len();
+		let first_uv = model.uvs.len();
+		let first_normal = model.normals.len();
+		
+		let mut triangles: Vec<[usize; 3]> = Vec::new();
+		let mut normals = vec![Vertex { x: 0.0, y: 0.0, z: 0.0 }; points.len()];
+		
+		for j in 0..points.len() / us - 1 {
+			for i in 0..us - 1 {
+				let (a, b, c, d) = (i + j * us, i + 1 + j * us, i + 1 + (j + 1) * us, i + (j + 1) * us);
+				
+				for triangle in &[[a, b, c], [a, c, d]] {
+					let normal = cross(
+						&vector(&points[triangle[0]], &points[triangle[1]]),
+						&vector(&points[triangle[0]], &points[triangle[2]])
+					);
+					
+					// degenerated triangles, where the surface collapse to a point
+					if !dot(&normal, &normal).is_normal() {
+						continue;
+					}
+					
+					for &corner in triangle {
+						normals[corner].x += normal.x;
+						normals[corner].y += normal.y;
+						normals[corner].z += normal.z;
+					}
+					
+					triangles.push(*triangle);
+				}
+			}
+		}
+		
+		for normal in &mut normals {
+			normalize(normal);
+		}
+		
+		let has_uvs = !uvs.is_empty();
+		
+		model.vertices.extend(points);
+		model.colors.extend(colors);
+		model.uvs.extend(uvs);
+		model.normals.extend(normals);
+		
+		for triangle in triangles {
+			model.faces.push(Face {
+				vertex: 	triangle.iter().map(|&corner| first_vertex + corner).collect(),
+				uv: 		if has_uvs { triangle.iter().map(|&corner| first_uv + corner).collect() } else { Vec::new() },
+				normal: 	triangle.iter().map(|&corner| first_normal + corner).collect(),
+				material: 	surface.material,
+				group: 		surface.group.clone(),
+				object: 	surface.object.clone(),
+				display: 	surface.display
+			});
+		}
+		
+	}
+	
+	if ignored > 0 {
+		println!(
+			"{} free-form curves or surfaces can't be evaluated (bmatrix basis, wrong number of control points or parameters), they will be ignored.",
+			ignored
+		);
+	}
+	
+	if trimmed > 0 {
+		println!("Trimming curves and holes (trim, hole) are not supported yet, {} trimmed surfaces will be ignored.", trimmed);
+	}
+	
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	use converter::{read_obj, fuzzy_cmp};
+	
+	#[test]
+	fn test_control_count() {
+	
+		assert!(control_count(Basis::Bezier, 3, &[0.0, 1.0]) == Some(4));
+		assert!(control_count(Basis::Bezier, 2, &[0.0, 1.0, 2.0]) == Some(5));
+		assert!(control_count(Basis::Bezier, 0, &[0.0, 1.0]).is_none());
+		assert!(control_count(Basis::BSpline, 3, &[0.0, 0.0, 0.0, 0.0, 0.5, 1.0, 1.0, 1.0, 1.0]) == Some(5));
+		assert!(control_count(Basis::BSpline, 3, &[0.0, 0.0, 1.0, 1.0]).is_none());
+		assert!(control_count(Basis::Cardinal, 3, &[0.0, 1.0, 2.0]) == Some(5));
+		assert!(control_count(Basis::Cardinal, 2, &[0.0, 1.0]).is_none());
+		assert!(control_count(Basis::Taylor, 2, &[0.0, 1.0, 2.0]) == Some(6));
+		assert!(control_count(Basis::BMatrix, 3, &[0.0, 1.0]).is_none());
+		assert!(control_count(Basis::Bezier, 3, &[0.0]).is_none());
+		
+	}
+	
+	#[test]
+	fn test_basis_functions() {
+	
+		let sum = |basis: &[(usize, f64)]| basis.iter().map(|value| value.1).sum::<f64>();
+		
+		let bezier = basis_functions(Basis::Bezier, 3, &[0.0, 1.0], 0.5);
+		
+		assert!(bezier == vec![(0, 0.125), (1, 0.375), (2, 0.375), (3, 0.125)]);
+		
+		let bezier = basis_functions(Basis::Bezier, 2, &[0.0, 1.0, 3.0], 3.0);
+		
+		assert!(bezier == vec![(2, 0.0), (3, 0.0), (4, 1.0)]);
+		
+		let knots = [0.0, 0.0, 0.0, 1.0, 2.0, 2.0, 2.0];
+		let bspline = basis_functions(Basis::BSpline, 2, &knots, 1.0);
+		
+		assert!(bspline == vec![(1, 0.5), (2, 0.5), (3, 0.0)]);
+		assert!(fuzzy_cmp(sum(&basis_functions(Basis::BSpline, 2, &knots, 0.3)), 1.0, 0.000001));
+		assert!(basis_functions(Basis::BSpline, 2, &knots, 2.0) == vec![(1, 0.0), (2, 0.0), (3, 1.0)]);
+		
+		let cardinal = basis_functions(Basis::Cardinal, 3, &[0.0, 1.0, 2.0], 1.0);
+		
+		assert!(cardinal == vec![(1, 0.0), (2, 1.0), (3, 0.0), (4, 0.0)]);
+		assert!(fuzzy_cmp(sum(&basis_functions(Basis::Cardinal, 3, &[0.0, 1.0], 0.3)), 1.0, 0.000001));
+		
+		let taylor = basis_functions(Basis::Taylor, 2, &[0.0, 1.0, 2.0], 1.5);
+		
+		assert!(taylor == vec![(3, 1.0), (4, 0.5), (5, 0.25)]);
+		
+	}
+	
+	#[test]
+	fn test_segments() {
+	
+		let arc = |t: f64| Vertex { x: t.cos(), y: t.sin(), z: 0.0 };
+		let half = ::std::f64::consts::PI;
+		
+		assert!(segments(None, 1, 5, &arc, 0.0, half) == 5);
+		assert!(segments(Some(Technique::Parametric(2.0, 3.0)), 0, 5, &arc, 0.0, half) == 2);
+		assert!(segments(Some(Technique::Space(1.0)), 0, 5, &arc, 0.0, half) == 4);
+		assert!(segments(Some(Technique::Space(0.0)), 0, 5, &arc, 0.0, half) == MAX_SEGMENTS);
+		assert!(segments(Some(Technique::Curvature(1.0, 45.0)), 0, 5, &arc, 0.0, half) == 4);
+		assert!(segments(Some(Technique::Curvature(0.01, 90.0)), 0, 5, &arc, 0.0, half) == 11);
+		assert!(segments(Some(Technique::Curvature(0.01, 90.0)), 0, 5, &|t: f64| Vertex { x: t, y: 0.0, z: 0.0 }, 0.0, 1.0) == 1);
+		
+	}
+	
+	#[test]
+	fn test_tessellate_curves() {
+	
+		// quarter of circle as a rational bezier, and a line as a cubic cardinal spline
+		let obj = "\
+			v 1.0 0.0 0.0\n\
+			v 1.0 1.0 0.0 0.70710678\n\
+			v 0.0 1.0 0.0\n\
+			v -1.0 0.0 0.0\n\
+			v 0.0 0.0 0.0\n\
+			v 1.0 0.0 0.0\n\
+			v 2.0 0.0 0.0\n\
+			v 3.0 0.0 0.0\n\
+			o arc\n\
+			cstype rat bezier\n\
+			deg 2\n\
+			ctech cparm 4\n\
+			curv 0.0 1.0 1 2 3\n\
+			parm u 0.0 1.0\n\
+			end\n\
+			o line\n\
+			cstype cardinal\n\
+			deg 3\n\
+			ctech cparm 2\n\
+			curv 0.0 2.0 4 5 6 7 8\n\
+			parm u 0.0 1.0 2.0\n\
+			end\n\
+			curv 0.0 1.0 1 2\n\
+			parm u 0.0 1.0\n\
+			end\n\
+		";
+		
+		let mut model = read_obj(obj.as_bytes());
+		
+		assert!(model.curves.len() == 3);
+		
+		tessellate(&mut model, 8);
+		
+		assert!(model.curves.is_empty());
+		assert!(model.lines.len() == 2);
+		assert!(model.lines[0].vertex == vec![8, 9, 10, 11, 12]);
+		assert!(model.lines[0].object == "arc");
+		assert!(model.lines[1].vertex.len() == 5);
+		assert!(model.vertices.len() == 18);
+		
+		for vertex in &model.vertices[8..13] {
+			assert!(fuzzy_cmp(distance(vertex, &Vertex { x: 0.0, y: 0.0, z: 0.0 }), 1.0, 0.000001));
+		}
+		
+		assert!(model.vertices[13] == Vertex { x: 0.0, y: 0.0, z: 0.0 });
+		assert!(fuzzy_cmp(model.vertices[14].x, 0.5, 0.000001));
+		assert!(model.vertices[17] == Vertex { x: 2.0, y: 0.0, z: 0.0 });
+		
+		let mut model = read_obj("\
+			v 0.0 0.0 0.0 1.0 0.0 0.0\n\
+			v 1.0 0.0 0.0 0.0 0.0 1.0\n\
+			cstype bezier\n\
+			deg 1\n\
+			curv 0.0 1.0 1 2\n\
+			parm u 0.0 1.0\n\
+			end\n\
+		".as_bytes());
+		
+		tessellate(&mut model, 2);
+		
+		assert!(model.colors.len() == model.vertices.len());
+		assert!(model.colors[3] == [0.5, 0.0, 0.5, 1.0]);
+		
+	}
+	
+	#[test]
+	fn test_tessellate_surfaces() {
+	
+		let obj = "\
+			v 0.0 0.0 0.0\n\
+			v 1.0 0.0 0.0\n\
+			v 0.0 1.0 0.0\n\
+			v 1.0 1.0 0.0\n\
+			vt 0.0 0.0\n\
+			vt 1.0 1.0\n\
+			usemtl patch\n\
+			cstype bezier\n\
+			deg 1 1\n\
+			surf 0.0 1.0 0.0 1.0 1/1 2/1 3/2 4/2\n\
+			parm u 0.0 1.0\n\
+			parm v 0.0 1.0\n\
+			end\n\
+			stech cparma 1 3\n\
+			surf 0.0 1.0 0.0 1.0 1 2 3 4\n\
+			parm u 0.0 1.0\n\
+			parm v 0.0 1.0\n\
+			end\n\
+		";
+		
+		let mut model = read_obj(obj.as_bytes());
+		
+		tessellate(&mut model, 2);
+		
+		assert!(model.surfaces.is_empty());
+		assert!(model.vertices.len() == 4 + 9 + 8);
+		assert!(model.uvs.len() == 2 + 9);
+		assert!(model.normals.len() == 9 + 8);
+		assert!(model.faces.len() == 8 + 6);
+		assert!(model.faces[0].vertex == vec![4, 5, 8]);
+		assert!(model.faces[0].uv == vec![2, 3, 6]);
+		assert!(model.faces[0].normal == vec![0, 1, 4]);
+		assert!(model.faces[0].material == 0);
+		assert!(model.faces[8].vertex == vec![13, 14, 16]);
+		assert!(model.faces[8].uv.is_empty());
+		assert!(model.vertices[8] == Vertex { x: 0.5, y: 0.5, z: 0.0 });
+		assert!(model.uvs[6] == Uv { u: 0.5, v: 0.5, w: 0.0 });
+		assert!(model.normals.iter().all(|normal| *normal == Vertex { x: 0.0, y: 0.0, z: 1.0 }));
+		
+	}
+	
+	#[test]
+	fn test_tessellate_trimmed_surfaces() {
+	
+		let obj = "\
+			v 0.0 0.0 0.0\n\
+			v 1.0 0.0 0.0\n\
+			v 0.0 1.0 0.0\n\
+			v 1.0 1.0 0.0\n\
+			vp 0.25 0.25\n\
+			vp 0.75 0.25\n\
+			vp 0.75 0.75\n\
+			cstype bezier\n\
+			deg 1\n\
+			curv2 1 2 3 1\n\
+			parm u 0.0 1.0 2.0 3.0\n\
+			end\n\
+			deg 1 1\n\
+			surf 0.0 1.0 0.0 1.0 1 2 3 4\n\
+			parm u 0.0 1.0\n\
+			parm v 0.0 1.0\n\
+			hole 0.0 3.0 1\n\
+			end\n\
+			surf 0.0 1.0 0.0 1.0 1 2 3 4\n\
+			parm u 0.0 1.0\n\
+			parm v 0.0 1.0\n\
+			end\n\
+		";
+		
+		let mut model = read_obj(obj.as_bytes());
+		
+		assert!(model.surfaces.len() == 2 && model.surfaces[0].trimmed);
+		
+		tessellate(&mut model, 1);
+		
+		// the patch with a hole is left out, not filled
+		assert!(model.surfaces.is_empty());
+		assert!(model.vertices.len() == 4 + 4);
+		assert!(model.faces.len() == 2);
+		assert!(model.faces.iter().all(|face| face.vertex.iter().all(|&vertex| vertex >= 4)));
+		
+	}
+	
+}
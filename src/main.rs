@@ -3,28 +3,115 @@ Convert Wavefront OBJ / MTL files into Three.js (JSON model version, to be used
 -------------------------
 How to use this converter
 -------------------------
-obj2three -i infile.obj -o outfile.js [-m "morphfiles*.obj"] [-c "morphcolors*.obj"] [-a center|centerxz|top|bottom|none] [-s smooth|flat] [-t ascii|binary] [-d invert|normal] [-b] [-e]
+obj2three -i infile.obj -o outfile.js [-m "morphfiles*.obj"] [-c "morphcolors*.obj"] [-a center|centerxz|top|bottom|unit|none] [--fit 1.0] [--align-parts object|group|none] [-s smooth|flat] [-t ascii|binary|scene] [--split object|group|none] [--material-model phong|standard|physical] [--tessellation 8] [--allow-csh] [--strict|--lenient] [-d invert|normal] [-b] [--bake-texture-to-vertex-colors] [--atlas 2048] [--max-texture-size 4096] [--resize-textures] [--texture-search-path dir] [--copy-textures dir] [--texture-url-prefix url] [-e]
 Notes:
     - flags
         -i infile.obj			input OBJ file
         -o outfile.js			output JS file
         -m "morphfiles*.obj"	morph OBJ files (can use wildcards, enclosed in quotes multiple patterns separate by space)
         -c "morphcolors*.obj"	morph colors OBJ files (can use wildcards, enclosed in quotes multiple patterns separate by space)
-        -a center|centerxz|top|bottom|none model alignment
+        -a center|centerxz|top|bottom|unit|none model alignment (unit = normalize into the unit cube)
+        --fit 1.0               uniformly scale the model so its largest dimension equals 1.0 (applied before the alignment)
+        --align-parts object|group|none center each object / group on its own bounding box (after the model alignment),
+                                its position is exported in the "pivots" array of the model
         -s smooth|flat			smooth = export vertex normals, flat = no normals (face normals computed in loader)
-        -t ascii|binary			export ascii or binary format (ascii has more features, binary just supports vertices, faces, normals, uvs and materials)
-        -d invert|normal		invert transparency
-        -b						bake material colors into face colors
-        -x 10.0                 scale and truncate
+        -t ascii|binary|scene	export ascii or binary format (ascii has more features, binary just supports vertices, faces, normals, uvs and materials)
+                                or a Three.js object / scene (JSON object format 4, to be used with the ObjectLoader)
+        --split object|group|none  with the scene format, write one mesh per object (o), per group (g, an element of several groups going to the first one), or merge everything into a single mesh
+        --material-model phong|standard|physical  with the scene format, export all the materials as MeshPhongMaterial,
+                                MeshStandardMaterial or MeshPhysicalMaterial instead of following their illum statements, deriving the missing values (see below)
+        --tessellation 8        number of segments of each span of the free-form curves and surfaces (curv, surf), unless they define ctech / stech
+        --allow-csh             run the shell commands of the csh statements (with sh), only use it with trusted files
+        --strict                stop at the first invalid statement of the OBJ / MTL files, with its line and column
+        --lenient               skip the invalid statements of the OBJ / MTL files, listing them with their line and column
+        -d invert|normal		invert transparency (the dissolve d of the MTL files being 0 for opaque materials)
+        -b						bake material colors into face colors (ascii) or vertex colors (scene), using a single material
+        --bake-texture-to-vertex-colors  bake the diffuse textures (map_Kd) into vertex colors, for targets without textures
+        --atlas 2048            pack the diffuse textures (map_Kd) into power of two atlases of at most 2048 x 2048 texels (see below)
+        --max-texture-size 4096 size above which the textures are reported as too large for WebGL
+        --resize-textures       resize the textures which aren't power of two sized, or are too large, into the output directory
+        --texture-search-path dir  search the textures in dir after the MTL and OBJ directories (can be repeated)
+        --copy-textures dir     copy the textures into dir, where the atlases and the resized textures are also written
+        --texture-url-prefix url  prefix of the texture references in the output, standing for the --copy-textures directory, like https://cdn.example.com/textures/
+        -x 10.0                 scale and truncate (vertices are multiplied by 10.0 and truncated to integers, the loader divides them back)
+        --precision 4 | 4,3,3   number of decimals of vertices, normals and uvs (one value for all, or vertices,normals,uvs)
         -f 2                    morph frame sampling step
     - by default:
         use smooth shading (if there were vertex normals in the original model)
         will be in ASCII format
         original model is assumed to use non-inverted transparency / dissolve (0.0 fully transparent, 1.0 fully opaque)
         no face colors baking
+        no texture baking
+        no texture atlas
+        textures larger than 4096 texels reported as too large, and not resized
+        textures searched in the MTL and OBJ directories, not copied, and referenced relatively to the output file
+        no fit to size
+        no alignment of the parts
+        one mesh per object with the scene format
+        materials exported following their PBR and illum statements
         no scale and truncate
+        vertices, normals and uvs written with 6 significant digits
+        free-form curves and surfaces spans divided into 8 segments
+        csh statements are ignored
+        invalid statements are skipped, only their summary being reported
         morph frame step = 1 (all files will be processed)
-    - binary conversion will create two files:
+    - the ascii model contains the bounding box, bounding sphere and oriented bounding box of the geometry
+      ("boundingBox", "boundingSphere" and "orientedBoundingBox", the rotation being a column-major 3x3 matrix)
+    - vertex colors (v x y z r g b [a], or ZBrush #MRGB polypaint) are exported in the "colors" array of the ascii model (without alpha)
+      or as the "color" attribute of the scene geometries, materials being set to use vertex colors
+    - 3D texture coordinates (vt u v w) are only exported by the scene format, as uv attributes of item size 3
+    - texture maps set with usemap create material variants (named material_map) using the map as diffuse texture,
+      maps being defined in the map libraries (maplib) by "newmap name" followed by "map file"
+    - materials with PBR statements (Pr, Pm, map_Pr, map_Pm) are exported by the scene format as MeshStandardMaterial,
+      or MeshPhysicalMaterial with Ps, Pc, Pcr, aniso or anisor (sheen, clearcoat, anisotropy); norm is the normal map.
+      The other ones follow their illumination model: illum 0 gives a MeshBasicMaterial (constant color), 1 a MeshLambertMaterial,
+      2 a MeshPhongMaterial, 3, 5 and 8 (reflections) a MeshPhongMaterial with a reflectivity of max(Ks),
+      4, 6, 7 and 9 (glass and refraction) a MeshPhysicalMaterial with a transmission of 1 - d (1 when opaque)
+      and 10 a ShadowMaterial; the ascii format sets the shading of the loader (basic, lambert or phong) and the reflectivity
+    - with --material-model standard or physical, the materials without PBR statements get a roughness derived from Ns
+      (1 - sqrt(Ns / 900), as Blender writes Ns = 900 (1 - roughness)^2), a metalness of max(Ks) with illum 3, 5 or 8,
+      1 when Ks is tinted (metals highlights) and 0 otherwise, and a base color mixing Kd with Ks by the metalness
+      (white with map_Kd and no Kd); physical materials use Ni as index of refraction.
+      With --material-model phong (and the ascii format), Ns is derived from Pr and the other PBR statements are ignored
+    - display and render statements are only exported by the scene format: lod groups the objects by level in a LOD object,
+      shadow_obj makes the meshes cast shadows, bevel, c_interp and d_interp are kept in the userData of the objects
+      and shadow_obj / trace_obj in the userData of the root (the statements without equivalent are reported)
+    - files included with call are read relatively to the calling file, their $1, $2... being replaced by the call arguments
+      (a file calling itself, directly or not, is skipped)
+    - invalid statements are unknown statements, values which aren't numbers, NaN or infinite values, wrong numbers of values,
+      invalid or out of range indices and references to skipped elements (a face using a skipped vertex is skipped,
+      the vertices keeping their index); empty indices (f 1/ 2/ 3/), tabs and CRLF line endings are valid,
+      and in OBJ and MTL files, # starts a comment up to the end of the line and a line ending with \ is continued on the next one
+    - the opacity of the materials is d, or 1 - Tr when they have no d statement (d takes precedence, as exporters
+      usually write both); transparent materials are blended without writing their depth (transparent, depthWrite false),
+      and opaque materials with an alpha texture (map_d) are cut out by the scene format (alphaTest 0.5), the ascii format
+      blending them; a map_d naming the map_Kd image uses its alpha channel instead of being exported as alpha map
+    - with -b, the diffuse color of the materials (their debug color without Kd), multiplied by the vertex colors,
+      is written as face colors by the ascii format (vertex colors when the model has some) and as vertex colors by the scene format
+      (the vertices shared by several materials being duplicated), all the faces using a single white material;
+      the textures and the transparency of the materials are dropped
+    - with --bake-texture-to-vertex-colors, the map_Kd textures (PNG, JPEG or TGA, found as described below)
+      are sampled with a bilinear filter at the texture coordinates of each vertex, averaged over the faces sharing it,
+      and multiplied into its vertex color (without alpha); the baked textures are removed from the materials
+    - with --atlas, the map_Kd textures are packed on shelves into power of two atlases (outfile_atlas_0.png, outfile_atlas_1.png...
+      written next to the output file, or into --copy-textures), with a padding of 4 texels repeating their borders; the texture coordinates are moved
+      into the atlases and the materials only differing by their texture are merged, saving draw calls. Repeating textures
+      (texture coordinates outside [0, 1]), textures larger than an atlas and materials with other textures are left out
+    - all the textures of the materials are checked: the ones which can't be read, aren't power of two sized (WebGL 1
+      can't use mipmaps or repeat them) or are larger than --max-texture-size are reported. With --resize-textures,
+      they are resampled to the nearest power of two (halved until they fit the maximum size) into the output directory
+      (or --copy-textures), as name_WIDTHxHEIGHT.ext, and the materials reference the resized textures
+    - the textures are searched, as written in the MTL file (or the map library), in the directory of the MTL file,
+      the one of the OBJ file, then the --texture-search-path ones; their directories and names are matched case-insensitively,
+      Windows separators (\) are supported, and absolute paths from another machine fall back on their file name.
+      The output references each texture where it is found, relatively to the output file and with the case of the file
+      on the disk; only the files written by the converter (copies, resized textures and atlases) are referenced by their
+      file name in their directory (numbered when several of them share it). --texture-url-prefix stands for the
+      --copy-textures directory (or the output one), so the textures copied there can be served from anywhere
+    - lines (l) and points (p) are only exported by the scene format, as LineSegments and Points
+    - free-form curves (curv) are tessellated into lines and surfaces (surf) into triangles,
+      for the bezier, bspline, cardinal and taylor bases (rational or not); surfaces with trimming curves or holes (trim, hole) are left out and reported
+    - binary conversion (not supported yet) will create two files:
         outfile.js  (materials)
         outfile.bin (binary buffers)
 --------------------------------------------------
@@ -81,9 +168,9 @@ How to get proper OBJ + MTL files with Blender
         - give your exported file name with "obj" extension
         - click on "Export OBJ" button
     4. Your model is now all files in this folder (OBJ, MTL, number of images)
-        - this converter assumes all files staying in the same folder,
-          (OBJ / MTL files use relative paths)
-        - for WebGL, textures must be power of 2 sized
+        - the textures are found wherever the OBJ / MTL files reference them (see above),
+          use --copy-textures to gather them next to the output file
+        - for WebGL, textures must be power of 2 sized (the converter reports the other ones, see --resize-textures)
 ------
 Author
 ------
@@ -91,37 +178,53 @@ Original author: AlteredQualia http://alteredqualia.com
 Rust port: Kao ..98 https://github.com/kao98/obj2three
 */
 
+
 extern crate argparse;
+extern crate image;
+#[cfg(test)]
+extern crate serde_json;
 
+use std::collections::HashMap;
 use std::str::FromStr;
 use std::fmt;
+use std::fs::File;
+use std::io::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
 
-use argparse::{ArgumentParser, StoreTrue, Store};
+use argparse::{ArgumentParser, StoreTrue, Store, StoreOption, Collect};
 
 mod converter;
+mod freeform;
+mod scene;
+mod texture;
 
 enum Alignment {
 	Center,
 	Centerxz,
 	Top,
 	Bottom,
+	Unit,
 	None
 }
 
 impl FromStr for Alignment {
 	type Err = ();
 	fn from_str(src: &str) -> Result<Alignment, ()> {
-		return match src {
+		match src {
 			"center" => Ok(Alignment::Center),
 			"centerxz" => Ok(Alignment::Centerxz),
 			"top" => Ok(Alignment::Top),
 			"bottom" => Ok(Alignment::Bottom),
+			"unit" => Ok(Alignment::Unit),
 			"none" => Ok(Alignment::None),
 			_ => Err(())
 		}
 	}
 }
 
+#[allow(clippy::match_ref_pats)]
 impl fmt::Display for Alignment {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match self {
@@ -129,11 +232,40 @@ impl fmt::Display for Alignment {
 			&Alignment::Centerxz => write!(f, "centerxz"),
 			&Alignment::Top => write!(f, "top"),
 			&Alignment::Bottom => write!(f, "bottom"),
+			&Alignment::Unit => write!(f, "unit"),
 			&Alignment::None => write!(f, "none")
 		}
 	}
 }
 
+enum Parts {
+	Object,
+	Group,
+	None
+}
+
+impl FromStr for Parts {
+	type Err = ();
+	fn from_str(src: &str) -> Result<Parts, ()> {
+		match src {
+			"object" => Ok(Parts::Object),
+			"group" => Ok(Parts::Group),
+			"none" => Ok(Parts::None),
+			_ => Err(())
+		}
+	}
+}
+
+impl fmt::Display for Parts {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Parts::Object => write!(f, "object"),
+			Parts::Group => write!(f, "group"),
+			Parts::None => write!(f, "none")
+		}
+	}
+}
+
 enum Smoothing {
 	Smooth,
 	Flat
@@ -142,7 +274,7 @@ enum Smoothing {
 impl FromStr for Smoothing {
 	type Err = ();
 	fn from_str(src: &str) -> Result<Smoothing, ()> {
-		return match src {
+		match src {
 			"smooth" => Ok(Smoothing::Smooth),
 			"flat" => Ok(Smoothing::Flat),
 			_ => Err(())
@@ -150,6 +282,7 @@ impl FromStr for Smoothing {
 	}
 }
 
+#[allow(clippy::match_ref_pats)]
 impl fmt::Display for Smoothing {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match self {
@@ -161,25 +294,29 @@ impl fmt::Display for Smoothing {
 
 enum OutputFormat {
 	Ascii,
-	Binary
+	Binary,
+	Scene
 }
 
 impl FromStr for OutputFormat {
 	type Err = ();
 	fn from_str(src: &str) -> Result<OutputFormat, ()> {
-		return match src {
+		match src {
 			"ascii" => Ok(OutputFormat::Ascii),
 			"binary" => Ok(OutputFormat::Binary),
+			"scene" => Ok(OutputFormat::Scene),
 			_ => Err(())
 		}
 	}
 }
 
+#[allow(clippy::match_ref_pats)]
 impl fmt::Display for OutputFormat {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match self {
 			&OutputFormat::Ascii => write!(f, "ascii"),
-			&OutputFormat::Binary => write!(f, "binary")
+			&OutputFormat::Binary => write!(f, "binary"),
+			&OutputFormat::Scene => write!(f, "scene")
 		}
 	}
 }
@@ -194,7 +331,7 @@ enum InvertTransparency {
 impl FromStr for InvertTransparency {
 	type Err = ();
 	fn from_str(src: &str) -> Result<InvertTransparency, ()> {
-		return match src {
+		match src {
 			"invert" => Ok(InvertTransparency::Invert),
 			"normal" => Ok(InvertTransparency::Normal),
 			_ => Err(())
@@ -202,6 +339,7 @@ impl FromStr for InvertTransparency {
 	}
 }
 
+#[allow(clippy::match_ref_pats)]
 impl fmt::Display for InvertTransparency {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match self {
@@ -217,11 +355,27 @@ struct Options {
 	morph_files: 			String,
 	morph_colors: 			String,
 	alignment: 				Alignment,
+	fit:					f32,
+	part_alignment:			Parts,
+	split:					Parts,
+	material_model:			Option<converter::MaterialModel>,
 	smoothing:				Smoothing,
 	output_format:			OutputFormat,
 	invert_transparency: 	InvertTransparency,
 	bake_material:			bool,
+	bake_texture:			bool,
+	atlas:					Option<u32>,
+	max_texture_size:		u32,
+	resize_textures:		bool,
+	texture_search_paths:	Vec<String>,
+	copy_textures:			Option<String>,
+	texture_url_prefix:		String,
 	scale:					f32,
+	precision:				converter::Precision,
+	tessellation:			usize,
+	allow_csh:				bool,
+	strict:					bool,
+	lenient:				bool,
 	morph:					i32
 }
 
@@ -233,11 +387,27 @@ fn main() {
 		morph_files: 			"".to_string(),
 		morph_colors: 			"".to_string(),
 		alignment: 				Alignment::None,
+		fit:					0.0,
+		part_alignment:			Parts::None,
+		split:					Parts::Object,
+		material_model:			None,
 		smoothing:				Smoothing::Smooth,
 		output_format:			OutputFormat::Ascii,
 		invert_transparency: 	InvertTransparency::Normal,
 		bake_material:			false,
+		bake_texture:			false,
+		atlas:					None,
+		max_texture_size:		4096,
+		resize_textures:		false,
+		texture_search_paths:	Vec::new(),
+		copy_textures:			None,
+		texture_url_prefix:		"".to_string(),
 		scale:					0.0,
+		precision:				converter::Precision::default(),
+		tessellation:			8,
+		allow_csh:				false,
+		strict:					false,
+		lenient:				false,
 		morph:					1
 	};
 	
@@ -294,7 +464,25 @@ fn main() {
 			.add_option(
 				&["-a"],
 				Store,
-				"center|centerxz|top|bottom|none model alignment"
+				"center|centerxz|top|bottom|unit|none model alignment - unit = normalize into the unit cube"
+			)
+		;
+		
+		ap
+			.refer(&mut options.fit)
+			.add_option(
+				&["--fit"],
+				Store,
+				"uniformly scale the model so its largest dimension equals the given size, before the alignment - Default, no scale (0.0)"
+			)
+		;
+		
+		ap
+			.refer(&mut options.part_alignment)
+			.add_option(
+				&["--align-parts"],
+				Store,
+				"object|group|none center each object or group on its own bounding box and export its pivot - Default none"
 			)
 		;
 		
@@ -312,7 +500,16 @@ fn main() {
 			.add_option(
 				&["-t"],
 				Store,
-				"ascii|binary export ascii or binary format (ascii has more features, binary just supports vertices, faces, normals, uvs and materials) - Default will be ascii."
+				"ascii|binary|scene export ascii or binary format (ascii has more features, binary just supports vertices, faces, normals, uvs and materials) or a Three.js object / scene (one mesh per object or group) - Default will be ascii."
+			)
+		;
+		
+		ap
+			.refer(&mut options.split)
+			.add_option(
+				&["--split"],
+				Store,
+				"object|group|none with the scene format, write one mesh per object, per group, or a single mesh - Default object"
 			)
 		;
 		
@@ -330,7 +527,70 @@ fn main() {
 			.add_option(
 				&["-b"],
 				StoreTrue,
-				"bake material colors into face colors"
+				"bake material colors into face colors (ascii) or vertex colors (scene), using a single material"
+			)
+		;
+		
+		ap
+			.refer(&mut options.bake_texture)
+			.add_option(
+				&["--bake-texture-to-vertex-colors"],
+				StoreTrue,
+				"bake the diffuse textures (map_Kd) into vertex colors, sampling them at the texture coordinates of the vertices"
+			)
+		;
+		
+		ap
+			.refer(&mut options.atlas)
+			.add_option(
+				&["--atlas"],
+				StoreOption,
+				"pack the diffuse textures (map_Kd) into power of two atlases of at most this size, written next to the output file"
+			)
+		;
+		
+		ap
+			.refer(&mut options.max_texture_size)
+			.add_option(
+				&["--max-texture-size"],
+				Store,
+				"size above which the textures are reported as too large for WebGL - Default 4096"
+			)
+		;
+		
+		ap
+			.refer(&mut options.resize_textures)
+			.add_option(
+				&["--resize-textures"],
+				StoreTrue,
+				"resize the textures which aren't power of two sized, or are too large, into the output directory"
+			)
+		;
+		
+		ap
+			.refer(&mut options.texture_search_paths)
+			.add_option(
+				&["--texture-search-path"],
+				Collect,
+				"directory searched for the textures after the ones of the MTL and OBJ files, can be repeated"
+			)
+		;
+		
+		ap
+			.refer(&mut options.copy_textures)
+			.add_option(
+				&["--copy-textures"],
+				StoreOption,
+				"copy the textures into this directory, where the atlases and the resized textures are also written - Default, no copy"
+			)
+		;
+		
+		ap
+			.refer(&mut options.texture_url_prefix)
+			.add_option(
+				&["--texture-url-prefix"],
+				Store,
+				"prefix of the texture references in the output, standing for the --copy-textures directory, like https://cdn.example.com/textures/ - Default, no prefix"
 			)
 		;
 		
@@ -339,7 +599,61 @@ fn main() {
 			.add_option(
 				&["-x"],
 				Store,
-				"scale and truncate - Default, no scale and truncate (1.0)"
+				"scale and truncate - Default, no scale and truncate"
+			)
+		;
+		
+		ap
+			.refer(&mut options.material_model)
+			.add_option(
+				&["--material-model"],
+				StoreOption,
+				"phong|standard|physical Three.js material of the scene format - Default, following the PBR and illum statements of each material"
+			)
+		;
+		
+		ap
+			.refer(&mut options.precision)
+			.add_option(
+				&["--precision"],
+				Store,
+				"number of decimals of vertices, normals and uvs, either one value for all or vertices,normals,uvs - Default 6 significant digits"
+			)
+		;
+		
+		ap
+			.refer(&mut options.tessellation)
+			.add_option(
+				&["--tessellation"],
+				Store,
+				"number of segments of each span of the free-form curves and surfaces without ctech / stech - Default 8"
+			)
+		;
+		
+		ap
+			.refer(&mut options.allow_csh)
+			.add_option(
+				&["--allow-csh"],
+				StoreTrue,
+				"run the shell commands of the csh statements - Default, csh statements are ignored"
+			)
+		;
+		
+		ap
+			.refer(&mut options.strict)
+			.add_option(
+				&["--strict"],
+				StoreTrue,
+				"stop at the first invalid statement - Default, invalid statements are skipped"
+			)
+		;
+		
+		ap
+			.refer(&mut options.lenient)
+			.add_option(
+				&["--lenient"],
+				StoreTrue,
+				"skip the invalid statements and list them - Default, only their summary is reported"
 			)
 		;
 		
@@ -352,21 +666,440 @@ fn main() {
 			)
 		;
 		
-		converter::parse_mtl("./test.txt");
-		
 		ap.parse_args_or_exit();
 	}
 	
-	println!("{}", options.input);
-	println!("{}", options.output);
-	println!("{}", options.morph_files);
-	println!("{}", options.morph_colors);
-	println!("{}", options.alignment);
-	println!("{}", options.smoothing);
-	println!("{}", options.output_format);
-	println!("{}", options.invert_transparency);
-	println!("{}", options.bake_material);
-	println!("{}", options.scale);
-	println!("{}", options.morph);
+	if options.strict && options.lenient {
+		println!("--strict and --lenient can't be used together.");
+		process::exit(1);
+	}
+	
+	if let OutputFormat::Binary = options.output_format {
+		println!("The binary format is not supported yet, use the ascii or the scene one.");
+		process::exit(1);
+	}
+	
+	convert(&options);
+	
+}
+
+/// Convert the given parts option into the converter one, `None` meaning no parts.
+fn part_option(parts: &Parts) -> Option<converter::PartOption> {
+	match *parts {
+		Parts::Object => Some(converter::PartOption::Object),
+		Parts::Group => Some(converter::PartOption::Group),
+		Parts::None => None
+	}
+}
+
+/// Find the textures of the materials (see texture::resolve_textures), reporting the ones which can't be found.
+/// Return the files of the textures found, by their reference.
+fn resolve_textures(materials: &mut [converter::Material], model: &converter::Model, options: &Options) -> HashMap<String, PathBuf> {
+
+	let directories = texture::search_directories(&options.input, &model.mtllib, &options.texture_search_paths);
+	let (sources, missing) = texture::resolve_textures(materials, &directories, &reference_base(options));
+	
+	for texture in missing {
+		println!(
+			"Couldn't find the texture [{}] in {}, use --texture-search-path to search other directories.",
+			texture,
+			directories.iter().map(|directory| format!("[{}]", directory.display())).collect::<Vec<String>>().join(", ")
+		);
+	}
+	
+	sources
+	
+}
+
+/// Return the directory the textures are written into: the --copy-textures one, or the one of the output file.
+fn texture_directory(options: &Options) -> PathBuf {
+	match options.copy_textures {
+		Some(ref directory) => PathBuf::from(directory),
+		None => Path::new(&options.output).parent().map_or(PathBuf::new(), Path::to_path_buf)
+	}
+}
+
+/// Return the directory the texture references are relative to: the one of the output file,
+/// or the texture directory when --texture-url-prefix stands for it.
+fn reference_base(options: &Options) -> PathBuf {
+	if options.texture_url_prefix.is_empty() {
+		Path::new(&options.output).parent().map_or(PathBuf::new(), Path::to_path_buf)
+	} else {
+		texture_directory(options)
+	}
+}
+
+/// Return the reference of a file written by the converter into the texture directory.
+fn written_reference(file_name: &str, options: &Options) -> String {
+	texture::relative_path(&reference_base(options), &texture_directory(options).join(file_name))
+}
+
+/// Load the diffuse textures of the materials, reporting the ones which can't be read
+/// (they are left out of the given `purpose`, like the ones which weren't found).
+fn load_textures(materials: &[converter::Material], sources: &HashMap<String, PathBuf>, purpose: &str) -> HashMap<String, image::RgbaImage> {
+
+	let mut maps: Vec<String> = materials.iter().filter_map(|material| material.map_diffuse.clone()).collect();
+	let mut textures = HashMap::new();
+	
+	maps.sort();
+	maps.dedup();
+	
+	for map in maps {
+		let path = match sources.get(&map) {
+			Some(path) => path,
+			None => continue
+		};
+		
+		match texture::load_texture(path) {
+			Ok(image) => { textures.insert(map, image); }
+			Err(error) => println!("{}, it won't be {}.", error, purpose)
+		}
+	}
+	
+	textures
+	
+}
+
+/// Check the textures of the materials for WebGL: report the ones which can't be read, and the ones which
+/// aren't power of two sized or are larger than --max-texture-size, resizing them into the texture directory
+/// with --resize-textures (they are added to the textures written by the converter, `generated`, which are skipped,
+/// and their file names to the ones `written` into the texture directory).
+fn audit_textures(materials: &mut [converter::Material], sources: &HashMap<String, PathBuf>, generated: &mut Vec<String>, written: &mut Vec<String>, options: &Options) {
+
+	let directory = texture_directory(options);
+	let mut renames = HashMap::new();
+	
+	for name in texture::texture_names(materials) {
+	
+		let path = match sources.get(&name) {
+			Some(path) if !generated.contains(&name) => path,
+			_ => continue
+		};
+		
+		let (width, height) = match image::image_dimensions(path) {
+			Ok(size) => size,
+			Err(error) => {
+				println!("Couldn't read the texture [{}] ({}).", path.display(), error);
+				continue;
+			}
+		};
+		
+		let (new_width, new_height) = match texture::power_of_two_size(width, height, options.max_texture_size) {
+			Some(size) => size,
+			None => continue
+		};
+		
+		let mut problems = Vec::new();
+		
+		if !width.is_power_of_two() || !height.is_power_of_two() {
+			problems.push("isn't power of two sized".to_string());
+		}
+		if width > options.max_texture_size || height > options.max_texture_size {
+			problems.push(format!("is larger than {} texels", options.max_texture_size));
+		}
+		
+		let problems = format!("[{}] ({} x {}) {}", name, width, height, problems.join(" and "));
+		
+		if !options.resize_textures {
+			println!("{}, use --resize-textures to resize it to {} x {}.", problems, new_width, new_height);
+			continue;
+		}
+		
+		let resized = texture::unique_file_name(&format!(
+			"{}_{}x{}.{}",
+			path.file_stem().map_or(String::new(), |stem| stem.to_string_lossy().into_owned()),
+			new_width,
+			new_height,
+			path.extension().map_or("png".to_string(), |extension| extension.to_string_lossy().into_owned())
+		), written);
+		
+		match texture::resize_texture(path, &directory.join(&resized), new_width, new_height) {
+			Ok(()) => {
+				let reference = written_reference(&resized, options);
+				
+				println!("{}, it is resized to {} x {} as [{}].", problems, new_width, new_height, reference);
+				written.push(resized);
+				generated.push(reference.clone());
+				renames.insert(name, reference);
+			}
+			Err(error) => println!("{}, [{}] is kept.", error, name)
+		}
+	
+	}
+	
+	texture::rename_textures(materials, &renames);
+	
+}
+
+/// Copy the textures of the materials into the --copy-textures directory, by their file name (numbered when
+/// it is already `written` there), except the ones written there by the converter (`generated`) and the ones
+/// which weren't found. The copied textures are referenced by their copy.
+fn copy_textures(materials: &mut [converter::Material], sources: &HashMap<String, PathBuf>, generated: &[String], written: &mut Vec<String>, options: &Options) {
+
+	let directory = texture_directory(options);
+	let mut renames = HashMap::new();
+	
+	for name in texture::texture_names(materials) {
+	
+		let source = match sources.get(&name) {
+			Some(source) if !generated.contains(&name) => source,
+			_ => continue
+		};
+		let file_name = texture::unique_file_name(&source.file_name().map_or(String::new(), |name| name.to_string_lossy().into_owned()), written);
+		let target = directory.join(&file_name);
+		
+		// a texture already in the directory isn't copied onto itself
+		let copied = match (source.canonicalize(), target.canonicalize()) {
+			(Ok(ref source), Ok(ref target)) if source == target => Ok(0),
+			_ => fs::copy(source, &target)
+		};
+		
+		match copied {
+			Ok(_) => {
+				renames.insert(name, written_reference(&file_name, options));
+				written.push(file_name);
+			}
+			Err(error) => println!("Couldn't copy [{}] to [{}] ({}).", source.display(), target.display(), error)
+		}
+	
+	}
+	
+	texture::rename_textures(materials, &renames);
+	
+}
+
+/// Report the issues of a parsed file: all of them with --lenient, only their summary otherwise.
+/// In strict mode, exit with the first one.
+fn check<T>(checked: converter::Checked<T>, file_name: &str, options: &Options) -> T {
+	match checked {
+		Ok((value, issues)) => {
+			if options.lenient {
+				for issue in &issues {
+					println!("{}", issue);
+				}
+			}
+			
+			if !issues.is_empty() {
+				println!(
+					"[{}] {}{}",
+					file_name,
+					converter::summarize_issues(&issues),
+					if options.lenient { "" } else { ", use --lenient to list them or --strict to stop at the first one" }
+				);
+			}
+			
+			value
+		}
+		Err(issue) => {
+			println!("{}", issue);
+			process::exit(1);
+		}
+	}
+}
+
+/// Convert the input OBJ file into an ASCII model or a scene, following the given options.
+fn convert(options: &Options) {
+
+	if !options.morph_files.is_empty() || !options.morph_colors.is_empty() || options.morph != 1 {
+		println!("Morph targets and morph colors are not supported yet, they will be ignored.");
+	}
+	
+	let settings = converter::ParseSettings {
+		allow_csh: 	options.allow_csh,
+		strict: 	options.strict
+	};
+	
+	let mut model = check(converter::parse_obj(&options.input, &settings), &options.input, options);
+	
+	freeform::tessellate(&mut model, options.tessellation);
+	
+	let non_finite_vertices = converter::find_non_finite_vertices(&model.vertices);
+	
+	if !non_finite_vertices.is_empty() {
+		println!(
+			"{} vertices have NaN or infinite coordinates (the first one is vertex {}), they are ignored by the alignment and the bounding volumes.",
+			non_finite_vertices.len(),
+			non_finite_vertices[0] + 1
+		);
+	}
+	
+	if options.fit > 0.0 {
+		converter::fit(&mut model.vertices, options.fit as f64);
+	}
+	
+	match options.alignment {
+		Alignment::Center => converter::center(&mut model.vertices),
+		Alignment::Centerxz => converter::center_xz(&mut model.vertices),
+		Alignment::Top => converter::align_top(&mut model.vertices),
+		Alignment::Bottom => converter::align_bottom(&mut model.vertices),
+		Alignment::Unit => converter::fit_unit_cube(&mut model.vertices),
+		Alignment::None => ()
+	}
+	
+	if let Some(by) = part_option(&options.part_alignment) {
+		converter::align_parts(&mut model, by);
+	}
+	
+	let mtl = check(converter::load_mtl(&model, &options.input, &settings), &model.mtllib, options);
+	let maps = converter::load_maplibs(&model, &options.input);
+	let mut materials = converter::create_materials(&model, &mtl, &maps);
+	
+	let invert = match options.invert_transparency { InvertTransparency::Invert => true, InvertTransparency::Normal => false };
+	
+	converter::resolve_transparency(&mut materials, invert);
+	
+	// the references are relative to the texture directory, which must exist
+	if let Some(ref directory) = options.copy_textures {
+		if let Err(error) = fs::create_dir_all(directory) {
+			println!("Couldn't create [{}] ({}).", directory, error);
+			process::exit(1);
+		}
+	}
+	
+	let sources = resolve_textures(&mut materials, &model, options);
+	
+	if options.bake_texture {
+		let textures = load_textures(&materials, &sources, "baked into the vertex colors");
+		
+		texture::bake_texture_colors(&mut model, &mut materials, &textures);
+	}
+	
+	// textures written by the converter, by their reference and by their file name in the texture directory
+	let mut generated = Vec::new();
+	let mut written = Vec::new();
+	
+	if let Some(size) = options.atlas {
+		if !size.is_power_of_two() {
+			println!("The size of the atlases must be a power of two.");
+			process::exit(1);
+		}
+		
+		let textures = load_textures(&materials, &sources, "packed into an atlas");
+		let name = Path::new(&options.output).file_stem().map_or("atlas".to_string(), |stem| format!("{}_atlas", stem.to_string_lossy()));
+		
+		let atlases = texture::build_atlases(&mut model, &mut materials, &textures, size, &name);
+		
+		for (texture, reason) in atlases.skipped {
+			println!("[{}] isn't packed into an atlas, as {}.", texture, reason);
+		}
+		for (file_name, image) in atlases.images {
+			let path = texture_directory(options).join(&file_name);
+			
+			if let Err(error) = image.save(&path) {
+				println!("Couldn't write [{}] ({}).", path.display(), error);
+			}
+			
+			let reference = written_reference(&file_name, options);
+			let renames = vec![(file_name.clone(), reference.clone())].into_iter().collect();
+			
+			texture::rename_textures(&mut materials, &renames);
+			generated.push(reference);
+			written.push(file_name);
+		}
+	}
+	
+	// the textures dropped by the color baking don't need to be checked, nor copied
+	if !options.bake_material {
+		audit_textures(&mut materials, &sources, &mut generated, &mut written, options);
+		
+		if options.copy_textures.is_some() {
+			copy_textures(&mut materials, &sources, &generated, &mut written, options);
+		}
+	}
+	
+	if !options.texture_url_prefix.is_empty() {
+		let renames = texture::texture_names(&materials).into_iter()
+			.map(|name| (name.clone(), format!("{}{}", options.texture_url_prefix, name)))
+			.collect();
+		
+		texture::rename_textures(&mut materials, &renames);
+	}
+	
+	let smooth = match options.smoothing { Smoothing::Smooth => true, Smoothing::Flat => false };
+	
+	let directives = converter::render_directives(&model);
+	
+	if let OutputFormat::Scene = options.output_format {
+		let unmapped: Vec<&str> = directives.into_iter().filter(|directive| *directive != "lod" && *directive != "shadow_obj").collect();
+		
+		if !unmapped.is_empty() {
+			println!("{} have no Three.js equivalent, they are only kept in the userData of the objects.", unmapped.join(", "));
+		}
+	} else if !directives.is_empty() {
+		println!("{} are not supported by the ascii format, they will be ignored (use -t scene).", directives.join(", "));
+	}
+	
+	if options.bake_material && materials.iter().any(|material| material.map_diffuse.is_some() || converter::alpha_mode(material) != converter::AlphaMode::Opaque) {
+		println!("The textures and the transparency of the materials are dropped by the color baking (-b).");
+	}
+	
+	let text = match options.output_format {
+		OutputFormat::Scene => {
+			if options.scale != 0.0 {
+				println!("Scale and truncate is not supported by the scene format, it will be ignored.");
+			}
+			
+			if let Some(by) = part_option(&options.part_alignment) {
+				if part_option(&options.split) != Some(by) {
+					println!(
+						"The parts are aligned by {} but the meshes are split by {}, their pivots will be ignored (use the same value for --align-parts and --split).",
+						options.part_alignment,
+						options.split
+					);
+				}
+			}
+			
+			if options.bake_material {
+				materials = converter::bake_vertex_colors(&mut model, &materials);
+			}
+			
+			let name = Path::new(&options.input).file_stem().map_or(String::new(), |stem| stem.to_string_lossy().into_owned());
+			
+			scene::generate_scene(&model, &materials, &name, &scene::SceneSettings {
+				smooth,
+				split: 		part_option(&options.split),
+				precision: 	options.precision,
+				material_model: options.material_model
+			})
+		}
+		_ => {
+			if !model.lines.is_empty() || !model.points.is_empty() {
+				println!("Lines (l) and points (p) are not supported by the ascii format, they will be ignored (use -t scene).");
+			}
+			if materials.iter().any(|material| converter::pbr_model(material) != converter::MaterialModel::Phong) {
+				println!("PBR material statements (Pr, Pm, Ps, Pc, Pcr, aniso, anisor, map_Pr, map_Pm) are not supported by the ascii format, they will be ignored, Pr being converted into Ns (use -t scene).");
+			}
+			if options.material_model.is_some() {
+				println!("The material model is not supported by the ascii format, it will be ignored (use -t scene).");
+			}
+			if model.uvs.iter().any(|uv| uv.w != 0.0) {
+				println!("3D texture coordinates (vt u v w) are not supported by the ascii format, w will be dropped (use -t scene).");
+			}
+			
+			let materials: Vec<converter::Material> = materials.iter()
+				.map(|material| converter::convert_material(material, converter::MaterialModel::Phong))
+				.collect();
+			
+			converter::generate_ascii_model(&model, &materials, &options.input, &converter::AsciiSettings {
+				smooth,
+				scale: 		if options.scale != 0.0 { Some(options.scale as f64) } else { None },
+				precision: 	options.precision,
+				bake_colors: options.bake_material
+			})
+		}
+	};
+	
+	let mut file = match File::create(&options.output) {
+		Ok(file) => file,
+		Err(error) => {
+			println!("Couldn't create [{}] ({}).", options.output, error);
+			process::exit(1);
+		}
+	};
+	
+	if let Err(error) = file.write_all(text.as_bytes()) {
+		println!("Couldn't write [{}] ({}).", options.output, error);
+		process::exit(1);
+	}
 	
 }
@@ -0,0 +1,1185 @@
+//! Three.js Object / Scene JSON writer (JSON object format 4, to be used with the ObjectLoader)
+//!
+//! Each mesh of the model is written as its own `BufferGeometry` and `Mesh`,
+//! all the meshes being children of a single `Group`.
+
+use std::collections::HashMap;
+
+use converter::{
+	Model, Material, MaterialModel, Face, Vertex, Display, Precision, PartOption,
+	split_model, material_model, convert_material, AlphaMode, alpha_mode, alpha_map,
+	triangulate, calculate_bounding_box, calculate_bounding_sphere,
+	format_float, generate_vertex, hex_color, json_string, COLORS
+};
+
+/// Settings of the scene generation.
+pub struct SceneSettings {
+	/// Export the vertex normals (smooth shading), otherwise the materials use flat shading
+	pub smooth: 	bool,
+	/// Write one mesh per object or group, or all the faces in a single mesh
+	pub split: 		Option<PartOption>,
+	pub precision: 	Precision,
+	/// Export all the materials with the given model (see [convert_material](../converter/fn.convert_material.html)),
+	/// otherwise each material uses the simplest model rendering its PBR statements
+	pub material_model: Option<MaterialModel>
+}
+
+/// A structure that represent one `BufferGeometry`: OBJ faces index vertices, uvs and
+/// normals separately, so each distinct combination becomes a vertex of the geometry.
+/// Triangles are sorted by material, `groups` giving the (start, count, material slot)
+/// of each material, and `materials` the model material used by each slot.
+#[derive(PartialEq)]
+pub struct Geometry {
+	pub positions: 	Vec<Vertex>,
+	pub normals: 	Vec<Vertex>,
+	pub uvs: 		Vec<[f64; 3]>,
+	pub colors: 	Vec<[f64; 4]>,
+	pub index: 		Vec<usize>,
+	pub groups: 	Vec<(usize, usize, usize)>,
+	pub materials: 	Vec<usize>
+}
+
+/// One corner of a triangle: the vertex, uv and normal indices in the model
+type Corner = (usize, Option<usize>, Option<usize>);
+
+/// Identifier of the n-th element of the given kind, the loader only needs them to be unique.
+fn uuid(kind: u32, index: usize) -> String {
+	format!("\"{:08X}-0000-4000-8000-{:012X}\"", kind, index)
+}
+
+/// This function build the geometry of the given faces of the model.
+/// Faces referencing a vertex that doesn't exist are skipped, and missing
+/// uvs or normals are written as zeros when other faces of the mesh have some.
+///
+/// # Examples
+///
+/// ```
+/// let model = read_obj("v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf 1 2 3 4\n".as_bytes());
+/// let geometry = build_geometry(&model, &model.faces, true);
+///
+/// assert!(geometry.positions.len() == 4);
+/// assert!(geometry.index == vec![0, 1, 2, 0, 2, 3]);
+/// ```
+pub fn build_geometry(model: &Model, faces: &[Face], smooth: bool) -> Geometry {
+
+	let mut triangles: Vec<(usize, Vec<Corner>)> = Vec::new();
+	
+	for face in triangulate(faces) {
+	
+		let corners = corners(model, &face, smooth);
+		
+		if corners.len() != face.vertex.len() {
+			continue;
+		}
+		
+		triangles.push((face.material, vec![corners[0], corners[1], corners[2]]));
+		
+		if corners.len() == 4 {
+			triangles.push((face.material, vec![corners[0], corners[2], corners[3]]));
+		}
+	
+	}
+	
+	build_primitives(model, triangles)
+	
+}
+
+/// This function build the geometry of the given lines of the model, to be drawn
+/// as `LineSegments`: each polyline is split into segments between consecutive vertices.
+///
+/// # Examples
+///
+/// ```
+/// let model = read_obj("v 0 0 0\nv 1 0 0\nv 1 1 0\nl 1 2 3\n".as_bytes());
+/// let geometry = build_line_geometry(&model, &model.lines);
+///
+/// assert!(geometry.index == vec![0, 1, 1, 2]);
+/// ```
+pub fn build_line_geometry(model: &Model, lines: &[Face]) -> Geometry {
+
+	let mut segments: Vec<(usize, Vec<Corner>)> = Vec::new();
+	
+	for line in lines {
+	
+		let corners = corners(model, line, false);
+		
+		if corners.len() != line.vertex.len() {
+			continue;
+		}
+		
+		for segment in corners.windows(2) {
+			segments.push((line.material, segment.to_vec()));
+		}
+	
+	}
+	
+	build_primitives(model, segments)
+	
+}
+
+/// This function build the geometry of the given points of the model, to be drawn as `Points`.
+///
+/// # Examples
+///
+/// ```
+/// let model = read_obj("v 0 0 0\nv 1 0 0\nv 1 1 0\np 1 3\n".as_bytes());
+/// let geometry = build_point_geometry(&model, &model.points);
+///
+/// assert!(geometry.positions.len() == 2);
+/// ```
+pub fn build_point_geometry(model: &Model, points: &[Face]) -> Geometry {
+
+	let mut primitives: Vec<(usize, Vec<Corner>)> = Vec::new();
+	
+	for point in points {
+	
+		let corners = corners(model, point, false);
+		
+		if corners.len() != point.vertex.len() {
+			continue;
+		}
+		
+		for &corner in &corners {
+			primitives.push((point.material, vec![corner]));
+		}
+	
+	}
+	
+	build_primitives(model, primitives)
+	
+}
+
+/// The corners of a face, line or point, without the ones referencing
+/// a vertex, uv or normal that doesn't exist.
+fn corners(model: &Model, face: &Face, smooth: bool) -> Vec<Corner> {
+
+	let has_uvs = face.uv.len() >= face.vertex.len();
+	let has_normals = smooth && face.normal.len() >= face.vertex.len();
+	
+	(0..face.vertex.len())
+		.map(|i| (
+			face.vertex[i],
+			if has_uvs { Some(face.uv[i]) } else { None },
+			if has_normals { Some(face.normal[i]) } else { None }
+		))
+		.filter(|&(vertex, uv, normal)| {
+			vertex < model.vertices.len() &&
+			uv.is_none_or(|uv| uv < model.uvs.len()) &&
+			normal.is_none_or(|normal| normal < model.normals.len())
+		})
+		.collect()
+	
+}
+
+/// Build a geometry from primitives (triangles, segments or points) given as
+/// their material and corners. Primitives are sorted by material, and each distinct
+/// corner becomes a vertex of the geometry.
+fn build_primitives(model: &Model, mut primitives: Vec<(usize, Vec<Corner>)>) -> Geometry {
+
+	// stable sort, so the primitives of a material keep their order
+	primitives.sort_by_key(|&(material, _)| material);
+	
+	let has_uvs = primitives.iter().any(|(_, corners)| corners.iter().any(|corner| corner.1.is_some()));
+	let has_normals = primitives.iter().any(|(_, corners)| corners.iter().any(|corner| corner.2.is_some()));
+	
+	let mut geometry = Geometry {
+		positions: 	Vec::new(),
+		normals: 	Vec::new(),
+		uvs: 		Vec::new(),
+		colors: 	Vec::new(),
+		index: 		Vec::new(),
+		groups: 	Vec::new(),
+		materials: 	Vec::new()
+	};
+	
+	let mut vertices: HashMap<Corner, usize> = HashMap::new();
+	
+	for &(material, ref corners) in &primitives {
+	
+		if geometry.materials.last() != Some(&material) {
+			geometry.groups.push((geometry.index.len(), 0, geometry.materials.len()));
+			geometry.materials.push(material);
+		}
+		
+		for corner in corners {
+		
+			let index = match vertices.get(corner) {
+				Some(&index) => index,
+				None => {
+					vertices.insert(*corner, geometry.positions.len());
+					geometry.positions.push(model.vertices[corner.0]);
+					if has_uvs {
+						let uv = corner.1.map_or([0.0, 0.0, 0.0], |uv| [model.uvs[uv].u, model.uvs[uv].v, model.uvs[uv].w]);
+						geometry.uvs.push(uv);
+					}
+					if has_normals {
+						let normal = corner.2.map_or(Vertex { x: 0.0, y: 0.0, z: 0.0 }, |normal| model.normals[normal]);
+						geometry.normals.push(normal);
+					}
+					if !model.colors.is_empty() {
+						geometry.colors.push(model.colors[corner.0]);
+					}
+					geometry.positions.len() - 1
+				}
+			};
+			
+			geometry.index.push(index);
+		
+		}
+		
+		if let Some(group) = geometry.groups.last_mut() {
+			group.1 += corners.len();
+		}
+	
+	}
+	
+	geometry
+	
+}
+
+/// Write the JSON of one buffer attribute.
+fn generate_attribute(item_size: usize, values: Vec<f64>, precision: Option<usize>) -> String {
+
+	let values: Vec<String> = values.iter().map(|&value| format_float(value, precision)).collect();
+	
+	format!(
+		"{{ \"itemSize\": {}, \"type\": \"Float32Array\", \"normalized\": false, \"array\": [{}] }}",
+		item_size,
+		values.join(",")
+	)
+
+}
+
+/// This function generate the JSON of one `BufferGeometry`, with its bounding box (`null` without
+/// finite positions) and its bounding sphere.
+pub fn generate_geometry(geometry: &Geometry, uuid: &str, name: &str, precision: &Precision) -> String {
+
+	let mut attributes = vec![format!(
+		"\"position\": {}",
+		generate_attribute(3, geometry.positions.iter().flat_map(|v| vec![v.x, v.y, v.z]).collect(), precision.vertices)
+	)];
+	
+	if !geometry.normals.is_empty() {
+		attributes.push(format!(
+			"\"normal\": {}",
+			generate_attribute(3, geometry.normals.iter().flat_map(|n| vec![n.x, n.y, n.z]).collect(), precision.normals)
+		));
+	}
+	
+	// 3D texture coordinates are only written when one of them has a w component
+	if !geometry.uvs.is_empty() {
+		let item_size = if geometry.uvs.iter().any(|uv| uv[2] != 0.0) { 3 } else { 2 };
+		
+		attributes.push(format!(
+			"\"uv\": {}",
+			generate_attribute(item_size, geometry.uvs.iter().flat_map(|uv| uv[..item_size].to_vec()).collect(), precision.uvs)
+		));
+	}
+	
+	// the alpha component is only written when a color is translucent
+	if !geometry.colors.is_empty() {
+		let item_size = if geometry.colors.iter().any(|color| color[3] < 1.0) { 4 } else { 3 };
+		
+		attributes.push(format!(
+			"\"color\": {}",
+			generate_attribute(item_size, geometry.colors.iter().flat_map(|color| color[..item_size].to_vec()).collect(), None)
+		));
+	}
+	
+	let index: Vec<String> = geometry.index.iter().map(|index| index.to_string()).collect();
+	
+	let groups: Vec<String> = geometry.groups.iter()
+		.map(|&(start, count, material)| format!("{{ \"start\": {}, \"count\": {}, \"materialIndex\": {} }}", start, count, material))
+		.collect();
+	
+	let bounding_box = match calculate_bounding_box(&geometry.positions) {
+		Some(bounding_box) => format!(
+			"{{ \"min\": [{}], \"max\": [{}] }}",
+			generate_vertex(&bounding_box.min, None, precision.vertices),
+			generate_vertex(&bounding_box.max, None, precision.vertices)
+		),
+		None => "null".to_string()
+	};
+	let bounding_sphere = calculate_bounding_sphere(&geometry.positions);
+	
+	format!(
+		"\t\t{{
+			\"uuid\": {},
+			\"type\": \"BufferGeometry\",
+			\"name\": {},
+			\"data\": {{
+				\"attributes\": {{
+					{}
+				}},
+				\"index\": {{ \"type\": \"Uint32Array\", \"array\": [{}] }},
+				\"groups\": [{}],
+				\"boundingBox\": {},
+				\"boundingSphere\": {{ \"center\": [{}], \"radius\": {} }}
+			}}
+		}}",
+		uuid,
+		json_string(name),
+		attributes.join(",\n\t\t\t\t\t"),
+		index.join(","),
+		groups.join(", "),
+		bounding_box,
+		generate_vertex(&bounding_sphere.center, None, precision.vertices),
+		format_float(bounding_sphere.radius, precision.vertices)
+	)
+
+}
+
+/// This function generate the JSON properties of the Three.js material
+/// corresponding to the given MTL material, as (name, JSON value) pairs.
+/// Textures are referenced through the uuids given by `texture`.
+/// The material is exported as a `MeshBasicMaterial`, `MeshLambertMaterial`, `MeshPhongMaterial`,
+/// `MeshStandardMaterial`, `MeshPhysicalMaterial` or `ShadowMaterial` following `model`, only the Phong one
+/// having the specular properties; the anisotropy rotation, a fraction of a turn in the MTL file, is converted
+/// into radians, the sheen uses a white color (Three.js one is black by default) and the optical density is
+/// the index of refraction of the physical model. A transmitting material ignores the dissolve, which gives
+/// its transmission, and the shadow material only keeps its color and opacity (it has no textures).
+/// Transparent materials are blended without depth writes, and alpha textures on opaque ones are cut out
+/// by an alpha test (see [alpha_mode](../converter/fn.alpha_mode.html)).
+pub fn material_properties<F>(material: &Material, index: usize, smooth: bool, model: MaterialModel, mut texture: F) -> Vec<(String, String)>
+	where F: FnMut(&str) -> String {
+	
+	let color = match material.color_diffuse {
+		Some(ref color) => hex_color(color),
+		None => COLORS[index % COLORS.len()]
+	};
+	
+	let material_type = match model {
+		MaterialModel::Basic => "\"MeshBasicMaterial\"",
+		MaterialModel::Lambert => "\"MeshLambertMaterial\"",
+		MaterialModel::Phong => "\"MeshPhongMaterial\"",
+		MaterialModel::Standard => "\"MeshStandardMaterial\"",
+		MaterialModel::Physical => "\"MeshPhysicalMaterial\"",
+		MaterialModel::Shadow => "\"ShadowMaterial\""
+	};
+	
+	let mut properties = vec![
+		("type".to_string(), material_type.to_string()),
+		("name".to_string(), json_string(&material.name)),
+		("color".to_string(), color.to_string())
+	];
+	
+	if model == MaterialModel::Shadow {
+		properties.push(("opacity".to_string(), format_float(material.transparency.unwrap_or(0.5), None)));
+		properties.push(("transparent".to_string(), "true".to_string()));
+		
+		return properties;
+	}
+	
+	if model == MaterialModel::Phong {
+		if let Some(ref specular) = material.color_specular {
+			properties.push(("specular".to_string(), hex_color(specular).to_string()));
+		}
+		if let Some(shininess) = material.specular_coef {
+			properties.push(("shininess".to_string(), format_float(shininess, None)));
+		}
+	}
+	
+	let values = [
+		("roughness", material.roughness),
+		("metalness", material.metalness),
+		("sheen", material.sheen),
+		("clearcoat", material.clearcoat),
+		("clearcoatRoughness", material.clearcoat_roughness),
+		("anisotropy", material.anisotropy),
+		("anisotropyRotation", material.anisotropy_rotation.map(|rotation| rotation * 2.0 * ::std::f64::consts::PI)),
+		("transmission", material.transmission),
+		("reflectivity", material.reflectivity)
+	];
+	
+	for &(name, value) in &values {
+		if let Some(value) = value {
+			properties.push((name.to_string(), format_float(value, None)));
+		}
+	}
+	
+	if material.sheen.is_some() {
+		properties.push(("sheenColor".to_string(), hex_color(&[1.0, 1.0, 1.0]).to_string()));
+	}
+	if let (MaterialModel::Physical, Some(ior)) = (model, material.optical_density) {
+		properties.push(("ior".to_string(), format_float(ior.clamp(1.0, 2.333), None)));
+	}
+	if material.transmission.is_none() {
+		if let Some(opacity) = material.transparency {
+			properties.push(("opacity".to_string(), format_float(opacity, None)));
+		}
+		match alpha_mode(material) {
+			AlphaMode::Blend => {
+				properties.push(("transparent".to_string(), "true".to_string()));
+				properties.push(("depthWrite".to_string(), "false".to_string()));
+			}
+			AlphaMode::Mask => properties.push(("alphaTest".to_string(), "0.5".to_string())),
+			AlphaMode::Opaque => ()
+		}
+	}
+	if !smooth && model != MaterialModel::Basic {
+		properties.push(("flatShading".to_string(), "true".to_string()));
+	}
+	
+	let lit = model != MaterialModel::Basic;
+	let maps = [
+		("map", &material.map_diffuse),
+		("specularMap", if model == MaterialModel::Phong || model == MaterialModel::Basic { &material.map_specular } else { &None }),
+		("bumpMap", if lit { &material.map_bump } else { &None }),
+		("normalMap", if lit { &material.map_normal } else { &None }),
+		("roughnessMap", &material.map_roughness),
+		("metalnessMap", &material.map_metalness),
+		("alphaMap", alpha_map(material))
+	];
+	
+	for &(name, map) in &maps {
+		if let Some(ref map) = *map {
+			properties.push((name.to_string(), texture(map)));
+		}
+	}
+	
+	properties
+
+}
+
+/// This function generate the JSON properties of the material used to draw
+/// lines (`LineBasicMaterial`) or points (`PointsMaterial`) with the given MTL material.
+pub fn basic_material_properties(material: &Material, index: usize, points: bool) -> Vec<(String, String)> {
+
+	let color = match material.color_diffuse {
+		Some(ref color) => hex_color(color),
+		None => COLORS[index % COLORS.len()]
+	};
+	
+	let mut properties = vec![
+		("type".to_string(), (if points { "\"PointsMaterial\"" } else { "\"LineBasicMaterial\"" }).to_string()),
+		("name".to_string(), json_string(&material.name)),
+		("color".to_string(), color.to_string())
+	];
+	
+	if points {
+		properties.push(("size".to_string(), "1".to_string()));
+		properties.push(("sizeAttenuation".to_string(), "false".to_string()));
+	}
+	if let Some(opacity) = material.transparency {
+		properties.push(("opacity".to_string(), format_float(opacity, None)));
+		properties.push(("transparent".to_string(), (opacity < 1.0).to_string()));
+	}
+	
+	properties
+	
+}
+
+/// The display attributes of the elements, written in the `userData` of their object
+/// as they have no Three.js equivalent (the level of detail also groups the objects in a `LOD`).
+fn display_user_data(display: &Display) -> Vec<(&'static str, String)> {
+
+	let mut user_data = Vec::new();
+	
+	if display.lod > 0 {
+		user_data.push(("lod", display.lod.to_string()));
+	}
+	if display.bevel {
+		user_data.push(("bevel", "true".to_string()));
+	}
+	if display.color_interpolation {
+		user_data.push(("c_interp", "true".to_string()));
+	}
+	if display.dissolve_interpolation {
+		user_data.push(("d_interp", "true".to_string()));
+	}
+	
+	user_data
+	
+}
+
+/// Write the `userData` property of an object, nothing when there is no data.
+fn generate_user_data(user_data: &[(&str, String)]) -> String {
+
+	if user_data.is_empty() {
+		return String::new();
+	}
+	
+	let properties: Vec<String> = user_data.iter()
+		.map(|(name, value)| format!("\"{}\": {}", name, value))
+		.collect();
+	
+	format!(", \"userData\": {{ {} }}", properties.join(", "))
+	
+}
+
+/// Write the JSON of one material.
+fn generate_material(uuid: &str, properties: &[(String, String)]) -> String {
+
+	let properties: Vec<String> = properties.iter()
+		.map(|(name, value)| format!("\t\t\t\"{}\": {}", name, value))
+		.collect();
+	
+	format!("\t\t{{\n\t\t\t\"uuid\": {},\n{}\n\t\t}}", uuid, properties.join(",\n"))
+	
+}
+
+/// This function generate the Three.js JSON object (format 4) of the given model:
+/// one `BufferGeometry` and one `Mesh` per object or group (see [split_model](../converter/fn.split_model.html)),
+/// plus one `LineSegments` for its lines and one `Points` for its points,
+/// all of them being children of a `Group` named after the source file.
+/// When a mesh has a pivot (see [align_parts](../converter/fn.align_parts.html)),
+/// the mesh is translated to that pivot, the parts being split the same way (by object or by group).
+/// Elements with different display attributes (`lod`, `bevel`, `c_interp`, `d_interp`) are written
+/// as separate objects carrying them in their `userData`; objects having a level of detail are grouped
+/// by level in a `LOD` object, level 100 being shown from distance 0 and level 1 from distance 99.
+/// Meshes cast shadows when the model has a `shadow_obj`.
+pub fn generate_scene(model: &Model, materials: &[Material], name: &str, settings: &SceneSettings) -> String {
+
+	let mut images: Vec<String> = Vec::new();
+	
+	// vertex colors are multiplied by the material color, which is white unless the MTL defines it
+	let vertex_colors = |mut properties: Vec<(String, String)>, material: &Material| {
+		if !model.colors.is_empty() {
+			if material.color_diffuse.is_none() {
+				for property in properties.iter_mut().filter(|property| property.0 == "color") {
+					property.1 = hex_color(&[1.0, 1.0, 1.0]).to_string();
+				}
+			}
+			properties.push(("vertexColors".to_string(), "true".to_string()));
+		}
+		properties
+	};
+	
+	let mut materials_string: Vec<String> = materials.iter().enumerate().map(|(index, material)| {
+	
+		let model = settings.material_model.unwrap_or_else(|| material_model(material));
+		let material = &convert_material(material, model);
+		
+		let properties = material_properties(material, index, settings.smooth, model, |url| {
+			let image = match images.iter().position(|image| image == url) {
+				Some(image) => image,
+				None => {
+					images.push(url.to_string());
+					images.len() - 1
+				}
+			};
+			uuid(3, image)
+		});
+		
+		generate_material(&uuid(2, index), &vertex_colors(properties, material))
+	
+	}).collect();
+	
+	let images_string: Vec<String> = images.iter().enumerate()
+		.map(|(index, url)| format!("\t\t{{ \"uuid\": {}, \"url\": {} }}", uuid(4, index), json_string(url)))
+		.collect();
+	
+	let textures_string: Vec<String> = (0..images.len())
+		.map(|index| format!("\t\t{{ \"uuid\": {}, \"image\": {}, \"wrap\": [1000, 1000] }}", uuid(3, index), uuid(4, index)))
+		.collect();
+	
+	let mut geometries: 		Vec<String> = Vec::new();
+	let mut line_materials: 	Vec<usize> 	= Vec::new();
+	let mut point_materials: 	Vec<usize> 	= Vec::new();
+	
+	// children of the root, with their level of detail
+	let mut children: 	Vec<(u32, String)> 	= Vec::new();
+	let mut objects 						= 0;
+	
+	for mesh in split_model(model, settings.split) {
+	
+		let position = match model.pivots.iter().find(|pivot| Some(pivot.part) == settings.split && pivot.name == mesh.name) {
+			Some(pivot) => pivot.position,
+			None => Vertex { x: 0.0, y: 0.0, z: 0.0 }
+		};
+		
+		// one object per kind of element and per display attributes
+		let mut displays: Vec<Display> = Vec::new();
+		
+		for element in mesh.faces.iter().chain(&mesh.lines).chain(&mesh.points) {
+			if !displays.contains(&element.display) {
+				displays.push(element.display);
+			}
+		}
+		
+		for display in displays {
+		
+			let select = |elements: &[Face]| -> Vec<Face> {
+				elements.iter().filter(|element| element.display == display).cloned().collect()
+			};
+			
+			let kinds = [
+				("Mesh", build_geometry(model, &select(&mesh.faces), settings.smooth)),
+				("LineSegments", build_line_geometry(model, &select(&mesh.lines))),
+				("Points", build_point_geometry(model, &select(&mesh.points)))
+			];
+			
+			for &(kind, ref geometry) in &kinds {
+			
+				if geometry.index.is_empty() {
+					continue;
+				}
+				
+				let geometry_uuid = uuid(1, geometries.len());
+				
+				geometries.push(generate_geometry(geometry, &geometry_uuid, &mesh.name, &settings.precision));
+				
+				let material_uuids: Vec<String> = geometry.materials.iter().map(|&material| {
+					let material = material.min(materials.len() - 1);
+					match kind {
+						"LineSegments" => {
+							if !line_materials.contains(&material) {
+								line_materials.push(material);
+							}
+							uuid(6, material)
+						}
+						"Points" => {
+							if !point_materials.contains(&material) {
+								point_materials.push(material);
+							}
+							uuid(7, material)
+						}
+						_ => uuid(2, material)
+					}
+				}).collect();
+				
+				let material = if material_uuids.len() == 1 {
+					material_uuids[0].clone()
+				} else {
+					format!("[{}]", material_uuids.join(", "))
+				};
+				
+				let mut properties = String::new();
+				
+				if kind == "Mesh" && !model.shadow_obj.is_empty() {
+					properties.push_str(", \"castShadow\": true");
+				}
+				properties.push_str(&generate_user_data(&display_user_data(&display)));
+				
+				objects += 1;
+				
+				children.push((display.lod, format!(
+					"\t\t\t{{ \"uuid\": {}, \"type\": \"{}\", \"name\": {}, \"geometry\": {}, \"material\": {}, \"matrix\": [1,0,0,0,0,1,0,0,0,0,1,0,{},1]{} }}",
+					uuid(5, objects),
+					kind,
+					json_string(&mesh.name),
+					geometry_uuid,
+					material,
+					generate_vertex(&position, None, settings.precision.vertices),
+					properties
+				)));
+			
+			}
+		
+		}
+	
+	}
+	
+	let mut root_children: Vec<String> = children.iter()
+		.filter(|child| child.0 == 0)
+		.map(|child| child.1.clone())
+		.collect();
+	
+	// objects having a level of detail are grouped by level in a LOD object, the most detailed level first
+	let mut levels: Vec<u32> = children.iter().map(|child| child.0).filter(|&lod| lod > 0).collect();
+	
+	levels.sort_by(|a, b| b.cmp(a));
+	levels.dedup();
+	
+	if !levels.is_empty() {
+	
+		let mut groups: Vec<String> = Vec::new();
+		let mut distances: Vec<String> = Vec::new();
+		
+		for lod in levels {
+		
+			let members: Vec<String> = children.iter()
+				.filter(|child| child.0 == lod)
+				.map(|child| format!("\t\t{}", child.1))
+				.collect();
+			
+			objects += 1;
+			
+			groups.push(format!(
+				"\t\t\t\t{{ \"uuid\": {}, \"type\": \"Group\", \"name\": \"lod {}\", \"matrix\": [1,0,0,0,0,1,0,0,0,0,1,0,0,0,0,1], \"children\": [\n{}\n\t\t\t\t] }}",
+				uuid(5, objects),
+				lod,
+				members.join(",\n")
+			));
+			distances.push(format!("{{ \"object\": {}, \"distance\": {}, \"hysteresis\": 0 }}", uuid(5, objects), 100 - lod));
+		
+		}
+		
+		objects += 1;
+		
+		root_children.push(format!(
+			"\t\t\t{{ \"uuid\": {}, \"type\": \"LOD\", \"name\": \"lod\", \"matrix\": [1,0,0,0,0,1,0,0,0,0,1,0,0,0,0,1], \"autoUpdate\": true, \"levels\": [{}], \"children\": [\n{}\n\t\t\t] }}",
+			uuid(5, objects),
+			distances.join(", "),
+			groups.join(",\n")
+		));
+	
+	}
+	
+	let mut user_data = Vec::new();
+	
+	if !model.shadow_obj.is_empty() {
+		user_data.push(("shadow_obj", json_string(&model.shadow_obj)));
+	}
+	if !model.trace_obj.is_empty() {
+		user_data.push(("trace_obj", json_string(&model.trace_obj)));
+	}
+	
+	for &material in &line_materials {
+		materials_string.push(generate_material(&uuid(6, material), &vertex_colors(basic_material_properties(&materials[material], material, false), &materials[material])));
+	}
+	for &material in &point_materials {
+		materials_string.push(generate_material(&uuid(7, material), &vertex_colors(basic_material_properties(&materials[material], material, true), &materials[material])));
+	}
+	
+	format!(
+"{{
+	\"metadata\": {{
+		\"version\": 4.5,
+		\"type\": \"Object\",
+		\"generator\": \"OBJConverter\"
+	}},
+	\"geometries\": [
+{}
+	],
+	\"materials\": [
+{}
+	],
+	\"textures\": [
+{}
+	],
+	\"images\": [
+{}
+	],
+	\"object\": {{
+		\"uuid\": {},
+		\"type\": \"Group\",
+		\"name\": {},
+		\"matrix\": [1,0,0,0,0,1,0,0,0,0,1,0,0,0,0,1]{},
+		\"children\": [
+{}
+		]
+	}}
+}}
+",
+		geometries.join(",\n"),
+		materials_string.join(",\n"),
+		textures_string.join(",\n"),
+		images_string.join(",\n"),
+		uuid(5, 0),
+		json_string(name),
+		generate_user_data(&user_data),
+		root_children.join(",\n")
+	)
+
+}
+
+/// The test module of the scene writer
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	use converter::{read_obj, Uv};
+	
+	#[test]
+	fn test_build_geometry() {
+	
+		let obj = "\
+			v 0.0 0.0 0.0\n\
+			v 1.0 0.0 0.0\n\
+			v 1.0 1.0 0.0\n\
+			v 0.0 1.0 0.0\n\
+			vt 0.0 0.0\n\
+			vt 1.0 1.0\n\
+			vn 0.0 0.0 1.0\n\
+			usemtl first\n\
+			f 1/1/1 2/1/1 3/2/1\n\
+			usemtl second\n\
+			f 1/1/1 3/1/1 4/1/1\n\
+			usemtl first\n\
+			f 1/1/1 2/1/1 9/1/1\n\
+			f 1 3 4\n\
+		";
+		
+		let model = read_obj(obj.as_bytes());
+		
+		let smooth = build_geometry(&model, &model.faces, true);
+		
+		assert!(smooth.positions == vec![
+			Vertex { x: 0.0, y: 0.0, z: 0.0 },
+			Vertex { x: 1.0, y: 0.0, z: 0.0 },
+			Vertex { x: 1.0, y: 1.0, z: 0.0 },
+			Vertex { x: 0.0, y: 0.0, z: 0.0 },
+			Vertex { x: 1.0, y: 1.0, z: 0.0 },
+			Vertex { x: 0.0, y: 1.0, z: 0.0 },
+			Vertex { x: 1.0, y: 1.0, z: 0.0 },
+			Vertex { x: 0.0, y: 1.0, z: 0.0 }
+		]);
+		assert!(smooth.uvs == vec![[0.0; 3], [0.0; 3], [1.0, 1.0, 0.0], [0.0; 3], [0.0; 3], [0.0; 3], [0.0; 3], [0.0; 3]]);
+		assert!(smooth.normals.len() == 8);
+		assert!(smooth.normals[0] == Vertex { x: 0.0, y: 0.0, z: 1.0 });
+		assert!(smooth.normals[3] == Vertex { x: 0.0, y: 0.0, z: 0.0 });
+		assert!(smooth.index == vec![0, 1, 2, 3, 4, 5, 0, 6, 7]);
+		assert!(smooth.groups == vec![(0, 6, 0), (6, 3, 1)]);
+		assert!(smooth.materials == vec![0, 1]);
+		
+		let flat = build_geometry(&model, &model.faces[..1], false);
+		
+		assert!(flat.positions.len() == 3);
+		assert!(flat.normals.is_empty());
+		assert!(flat.uvs.len() == 3);
+	
+	}
+	
+	#[test]
+	fn test_build_line_and_point_geometry() {
+	
+		let obj = "\
+			v 0.0 0.0 0.0\n\
+			v 1.0 0.0 0.0\n\
+			v 1.0 1.0 0.0\n\
+			vt 0.5 0.5\n\
+			usemtl first\n\
+			l 1 2 3\n\
+			usemtl second\n\
+			l 3/1 1/1\n\
+			l 3 9\n\
+			p 1 2\n\
+			p 2 3\n\
+		";
+		
+		let model = read_obj(obj.as_bytes());
+		
+		let lines = build_line_geometry(&model, &model.lines);
+		
+		assert!(lines.positions.len() == 5);
+		assert!(lines.index == vec![0, 1, 1, 2, 3, 4]);
+		assert!(lines.uvs == vec![[0.0; 3], [0.0; 3], [0.0; 3], [0.5, 0.5, 0.0], [0.5, 0.5, 0.0]]);
+		assert!(lines.normals.is_empty());
+		assert!(lines.groups == vec![(0, 4, 0), (4, 2, 1)]);
+		
+		let points = build_point_geometry(&model, &model.points);
+		
+		assert!(points.positions.len() == 3);
+		assert!(points.index == vec![0, 1, 1, 2]);
+		assert!(points.groups == vec![(0, 4, 0)]);
+		assert!(points.materials == vec![1]);
+		
+	}
+	
+	#[test]
+	fn test_basic_material_properties() {
+	
+		let material = Material { name: "wire".to_string(), color_diffuse: Some([0.0, 1.0, 0.0]), ..Material::default() };
+		
+		let line = basic_material_properties(&material, 0, false);
+		let points = basic_material_properties(&material, 0, true);
+		
+		assert!(line == vec![
+			("type".to_string(), "\"LineBasicMaterial\"".to_string()),
+			("name".to_string(), "\"wire\"".to_string()),
+			("color".to_string(), "65280".to_string())
+		]);
+		assert!(points[0].1 == "\"PointsMaterial\"");
+		assert!(points.contains(&("sizeAttenuation".to_string(), "false".to_string())));
+		
+	}
+	
+	#[test]
+	fn test_material_properties() {
+	
+		let material = Material {
+			name: "glass".to_string(),
+			color_diffuse: Some([1.0, 0.0, 0.0]),
+			specular_coef: Some(50.0),
+			transparency: Some(0.5),
+			map_diffuse: Some("glass.png".to_string()),
+			map_alpha: Some("glass_alpha.png".to_string()),
+			..Material::default()
+		};
+		
+		let mut textures = Vec::new();
+		
+		let properties = material_properties(&material, 0, false, MaterialModel::Phong, |url| {
+			textures.push(url.to_string());
+			format!("\"{}\"", textures.len())
+		});
+		
+		assert!(properties == vec![
+			("type".to_string(), "\"MeshPhongMaterial\"".to_string()),
+			("name".to_string(), "\"glass\"".to_string()),
+			("color".to_string(), "16711680".to_string()),
+			("shininess".to_string(), "50".to_string()),
+			("opacity".to_string(), "0.5".to_string()),
+			("transparent".to_string(), "true".to_string()),
+			("depthWrite".to_string(), "false".to_string()),
+			("flatShading".to_string(), "true".to_string()),
+			("map".to_string(), "\"1\"".to_string()),
+			("alphaMap".to_string(), "\"2\"".to_string())
+		]);
+		assert!(textures == vec!["glass.png".to_string(), "glass_alpha.png".to_string()]);
+		
+		let default = material_properties(&Material::default(), 1, true, MaterialModel::Phong, |_| String::new());
+		
+		assert!(default[2] == ("color".to_string(), COLORS[1].to_string()));
+		
+		let material = Material {
+			name: "paint".to_string(),
+			color_specular: Some([1.0, 1.0, 1.0]),
+			map_specular: Some("specular.png".to_string()),
+			roughness: Some(0.5),
+			clearcoat: Some(1.0),
+			sheen: Some(0.25),
+			anisotropy_rotation: Some(0.5),
+			map_normal: Some("normal.png".to_string()),
+			map_metalness: Some("metalness.png".to_string()),
+			..Material::default()
+		};
+		
+		let properties = material_properties(&material, 0, true, material_model(&material), json_string);
+		
+		// the Phong specular properties are dropped
+		assert!(properties == vec![
+			("type".to_string(), "\"MeshPhysicalMaterial\"".to_string()),
+			("name".to_string(), "\"paint\"".to_string()),
+			("color".to_string(), COLORS[0].to_string()),
+			("roughness".to_string(), "0.5".to_string()),
+			("sheen".to_string(), "0.25".to_string()),
+			("clearcoat".to_string(), "1".to_string()),
+			("anisotropyRotation".to_string(), "3.14159".to_string()),
+			("sheenColor".to_string(), "16777215".to_string()),
+			("normalMap".to_string(), "\"normal.png\"".to_string()),
+			("metalnessMap".to_string(), "\"metalness.png\"".to_string())
+		]);
+		
+		let material = Material {
+			name: "glass".to_string(),
+			illumination: Some(4),
+			transparency: Some(0.25),
+			optical_density: Some(1.5),
+			map_bump: Some("bump.png".to_string()),
+			..Material::default()
+		};
+		
+		let model = material_model(&material);
+		let properties = material_properties(&convert_material(&material, model), 0, true, model, json_string);
+		
+		// the dissolve gives the transmission instead of the opacity
+		assert!(properties == vec![
+			("type".to_string(), "\"MeshPhysicalMaterial\"".to_string()),
+			("name".to_string(), "\"glass\"".to_string()),
+			("color".to_string(), COLORS[0].to_string()),
+			("metalness".to_string(), "0".to_string()),
+			("transmission".to_string(), "0.75".to_string()),
+			("ior".to_string(), "1.5".to_string()),
+			("bumpMap".to_string(), "\"bump.png\"".to_string())
+		]);
+		
+		let material = Material { illumination: Some(0), ..material };
+		let properties = material_properties(&material, 0, false, material_model(&material), json_string);
+		
+		// the constant color isn't lit
+		assert!(properties == vec![
+			("type".to_string(), "\"MeshBasicMaterial\"".to_string()),
+			("name".to_string(), "\"glass\"".to_string()),
+			("color".to_string(), COLORS[0].to_string()),
+			("opacity".to_string(), "0.25".to_string()),
+			("transparent".to_string(), "true".to_string()),
+			("depthWrite".to_string(), "false".to_string())
+		]);
+		
+		let material = Material { illumination: Some(10), color_diffuse: Some([0.0, 0.0, 0.0]), transparency: None, ..material };
+		let properties = material_properties(&material, 0, true, material_model(&material), json_string);
+		
+		assert!(properties == vec![
+			("type".to_string(), "\"ShadowMaterial\"".to_string()),
+			("name".to_string(), "\"glass\"".to_string()),
+			("color".to_string(), "0".to_string()),
+			("opacity".to_string(), "0.5".to_string()),
+			("transparent".to_string(), "true".to_string())
+		]);
+		
+		// alpha textures of opaque materials are cut out
+		let material = Material {
+			name: "leaf".to_string(),
+			transparency: Some(1.0),
+			map_diffuse: Some("leaf.png".to_string()),
+			map_alpha: Some("leaf.png".to_string()),
+			..Material::default()
+		};
+		let properties = material_properties(&material, 0, true, MaterialModel::Phong, json_string);
+		
+		assert!(properties == vec![
+			("type".to_string(), "\"MeshPhongMaterial\"".to_string()),
+			("name".to_string(), "\"leaf\"".to_string()),
+			("color".to_string(), COLORS[0].to_string()),
+			("opacity".to_string(), "1".to_string()),
+			("alphaTest".to_string(), "0.5".to_string()),
+			("map".to_string(), "\"leaf.png\"".to_string())
+		]);
+		
+		let material = Material { illumination: Some(3), color_specular: Some([0.5, 0.25, 0.25]), ..Material::default() };
+		let model = material_model(&material);
+		let properties = material_properties(&convert_material(&material, model), 0, true, model, json_string);
+		
+		assert!(properties.contains(&("type".to_string(), "\"MeshPhongMaterial\"".to_string())));
+		assert!(properties.contains(&("reflectivity".to_string(), "0.5".to_string())));
+	
+	}
+	
+	#[test]
+	fn test_generate_scene_display() {
+	
+		let model = read_obj("\
+			shadow_obj shadow.obj\n\
+			v 0.0 0.0 0.0\n\
+			v 1.0 0.0 0.0\n\
+			v 1.0 1.0 0.0\n\
+			o part\n\
+			f 1 2 3\n\
+			lod 100\n\
+			bevel on\n\
+			f 1 2 3\n\
+			lod 20\n\
+			f 3 2 1\n\
+			l 1 2\n\
+		".as_bytes());
+		
+		let materials = [Material { name: "default".to_string(), ..Material::default() }];
+		
+		let scene = generate_scene(&model, &materials, "model", &SceneSettings {
+			smooth: 	true,
+			split: 		Some(PartOption::Object),
+			precision: 	Precision::default(),
+			material_model: None
+		});
+		
+		assert!(scene.matches("\"type\": \"Mesh\"").count() == 3);
+		assert!(scene.matches("\"castShadow\": true").count() == 3);
+		assert!(scene.contains("\"matrix\": [1,0,0,0,0,1,0,0,0,0,1,0,0,0,0,1], \"userData\": { \"shadow_obj\": \"shadow.obj\" }"));
+		assert!(scene.contains("\"userData\": { \"lod\": 100, \"bevel\": true }"));
+		assert!(scene.contains("\"type\": \"LineSegments\", \"name\": \"part\", \"geometry\": \"00000001-0000-4000-8000-000000000003\", \"material\": \"00000006-0000-4000-8000-000000000000\", \"matrix\": [1,0,0,0,0,1,0,0,0,0,1,0,0,0,0,1], \"userData\": { \"lod\": 20, \"bevel\": true } }"));
+		assert!(scene.contains("\"type\": \"LOD\""));
+		assert!(scene.contains("\"levels\": [{ \"object\": \"00000005-0000-4000-8000-000000000005\", \"distance\": 0, \"hysteresis\": 0 }, { \"object\": \"00000005-0000-4000-8000-000000000006\", \"distance\": 80, \"hysteresis\": 0 }]"));
+		assert!(scene.contains("\"name\": \"lod 20\""));
+	
+	}
+	
+	#[test]
+	fn test_generate_scene() {
+	
+		let mut model = read_obj("\
+			v 0.0 0.0 0.0\n\
+			v 1.0 0.0 0.0\n\
+			v 1.0 1.0 0.0\n\
+			o first\n\
+			f 1 2 3\n\
+			o second\n\
+			f 3 2 1\n\
+			l 1 2\n\
+			p 3\n\
+		".as_bytes());
+		
+		model.pivots.push(::converter::Pivot { name: "second".to_string(), part: PartOption::Object, position: Vertex { x: 1.0, y: 2.0, z: 3.0 } });
+		// a group pivot doesn't apply to the object of the same name
+		model.pivots.push(::converter::Pivot { name: "first".to_string(), part: PartOption::Group, position: Vertex { x: 4.0, y: 5.0, z: 6.0 } });
+		
+		let materials = [Material { name: "default".to_string(), ..Material::default() }];
+		
+		let split = generate_scene(&model, &materials, "model", &SceneSettings {
+			smooth: 	true,
+			split: 		Some(PartOption::Object),
+			precision: 	Precision::default(),
+			material_model: None
+		});
+		let merged = generate_scene(&model, &materials, "model", &SceneSettings {
+			smooth: 	true,
+			split: 		None,
+			precision: 	Precision::default(),
+			material_model: None
+		});
+		
+		assert!(split.matches("\"type\": \"BufferGeometry\"").count() == 4);
+		assert!(split.matches("\"type\": \"Mesh\"").count() == 2);
+		assert!(split.contains("\"type\": \"Mesh\", \"name\": \"second\", \"geometry\": \"00000001-0000-4000-8000-000000000001\""));
+		assert!(split.contains("\"type\": \"LineSegments\", \"name\": \"second\", \"geometry\": \"00000001-0000-4000-8000-000000000002\", \"material\": \"00000006-0000-4000-8000-000000000000\""));
+		assert!(split.contains("\"type\": \"Points\", \"name\": \"second\", \"geometry\": \"00000001-0000-4000-8000-000000000003\", \"material\": \"00000007-0000-4000-8000-000000000000\""));
+		assert!(split.contains("\"type\": \"LineBasicMaterial\""));
+		assert!(split.contains("\"matrix\": [1,0,0,0,0,1,0,0,0,0,1,0,1,2,3,1]"));
+		assert!(!split.contains("\"matrix\": [1,0,0,0,0,1,0,0,0,0,1,0,4,5,6,1]"));
+		assert!(!merged.contains("\"matrix\": [1,0,0,0,0,1,0,0,0,0,1,0,1,2,3,1]"));
+		assert!(merged.matches("\"type\": \"BufferGeometry\"").count() == 3);
+		assert!(merged.contains("\"boundingBox\": { \"min\": [0,0,0], \"max\": [1,1,0] },\n\t\t\t\t\"boundingSphere\""));
+		assert!(merged.contains("\"boundingBox\": { \"min\": [1,1,0], \"max\": [1,1,0] }"));
+		assert!(merged.contains("\"index\": { \"type\": \"Uint32Array\", \"array\": [0,1,2,2,1,0] }"));
+		assert!(!merged.contains("vertexColors"));
+		
+		let standard = generate_scene(&model, &materials, "model", &SceneSettings {
+			smooth: 	true,
+			split: 		None,
+			precision: 	Precision::default(),
+			material_model: Some(MaterialModel::Standard)
+		});
+		
+		assert!(standard.contains("\"type\": \"MeshStandardMaterial\""));
+		assert!(standard.contains("\"metalness\": 0"));
+		
+		model.colors = vec![[1.0, 0.0, 0.0, 1.0], [0.0, 1.0, 0.0, 1.0], [0.0, 0.0, 1.0, 1.0]];
+		
+		let colored = generate_scene(&model, &materials, "model", &SceneSettings {
+			smooth: 	true,
+			split: 		None,
+			precision: 	Precision::default(),
+			material_model: None
+		});
+		
+		assert!(colored.contains("\"color\": { \"itemSize\": 3, \"type\": \"Float32Array\", \"normalized\": false, \"array\": [1,0,0,0,1,0,0,0,1] }"));
+		assert!(colored.contains("\"color\": 16777215"));
+		assert!(colored.matches("\"vertexColors\": true").count() == 3);
+		
+		model.colors[1][3] = 0.5;
+		
+		let translucent = generate_scene(&model, &materials, "model", &SceneSettings {
+			smooth: 	true,
+			split: 		None,
+			precision: 	Precision::default(),
+			material_model: None
+		});
+		
+		assert!(translucent.contains("\"itemSize\": 4, \"type\": \"Float32Array\", \"normalized\": false, \"array\": [1,0,0,1,0,1,0,0.5,0,0,1,1]"));
+	
+	}
+	
+	#[test]
+	fn test_generate_scene_3d_uvs() {
+	
+		let mut model = read_obj("\
+			v 0.0 0.0 0.0\n\
+			v 1.0 0.0 0.0\n\
+			v 1.0 1.0 0.0\n\
+			f 1 2 3\n\
+		".as_bytes());
+		
+		let materials = [Material { name: "default".to_string(), ..Material::default() }];
+		let settings = SceneSettings {
+			smooth: 	true,
+			split: 		None,
+			precision: 	Precision::default(),
+			material_model: None
+		};
+		
+		assert!(!generate_scene(&model, &materials, "model", &settings).contains("\"uv\""));
+		
+		model.uvs = vec![Uv { u: 0.5, v: 0.5, w: 0.25 }];
+		
+		for face in &mut model.faces {
+			face.uv = vec![0, 0, 0];
+		}
+		
+		let textured = generate_scene(&model, &materials, "model", &settings);
+		
+		assert!(textured.contains("\"uv\": { \"itemSize\": 3, \"type\": \"Float32Array\", \"normalized\": false, \"array\": [0.5,0.5,0.25,0.5,0.5,0.25,0.5,0.5,0.25]"));
+	
+	}
+
+}
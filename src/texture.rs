@@ -0,0 +1,1071 @@
+//! Textures decoding, sampling and packing
+//!
+//! The diffuse textures of the materials (PNG, JPEG or TGA) are decoded so their
+//! colors can be baked into the vertex colors of the model, for targets without textures,
+//! or packed into atlases, so the materials differing only by their texture can be merged.
+//! All the textures can also be checked (and resized) for WebGL, which needs power of two sizes.
+//! The textures are found next to the MTL file, the OBJ file or in the search paths,
+//! whatever the case and the separators of their paths (see [find_texture](./fn.find_texture.html)),
+//! and referenced where they are found (see [resolve_textures](./fn.resolve_textures.html)).
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use image::{self, RgbaImage};
+use image::imageops::FilterType;
+
+use converter::{Model, Material, Uv};
+
+/// Padding around the textures of an atlas, in texels, filled with their border texels
+/// so the filtering (and the mipmaps) don't mix neighbour textures.
+const ATLAS_PADDING: u32 = 4;
+
+/// Tolerance on the texture coordinates of the atlas textures, which must be between 0 and 1
+const UV_EPSILON: f64 = 1e-6;
+
+/// This function return the directories searched for the textures, in this order: the one of the MTL file,
+/// the one of the OBJ file (the MTL file being relative to it, see [load_mtl](../converter/fn.load_mtl.html)),
+/// then the given search paths. Each directory is listed once.
+///
+/// # Examples
+///
+/// ```
+/// let directories = search_directories("models/house.obj", "materials\\house.mtl", &["textures".to_string()]);
+///
+/// assert!(directories == vec![PathBuf::from("models/materials"), PathBuf::from("models"), PathBuf::from("textures")]);
+/// ```
+pub fn search_directories(obj_file_name: &str, mtllib: &str, search_paths: &[String]) -> Vec<PathBuf> {
+
+	let obj_directory = match Path::new(obj_file_name).parent() {
+		Some(parent) if parent != Path::new("") => parent.to_path_buf(),
+		_ => PathBuf::from(".")
+	};
+	let mut directories = Vec::new();
+	
+	if !mtllib.is_empty() {
+		if let Some(parent) = obj_directory.join(mtllib.replace("\\", "/")).parent() {
+			directories.push(parent.to_path_buf());
+		}
+	}
+	
+	directories.push(obj_directory);
+	directories.extend(search_paths.iter().map(|path| PathBuf::from(path.replace("\\", "/"))));
+	
+	let mut unique: Vec<PathBuf> = Vec::new();
+	
+	for directory in directories {
+		if !unique.contains(&directory) {
+			unique.push(directory);
+		}
+	}
+	
+	unique
+	
+}
+
+/// This function join the given path components to the directory, matching each one
+/// case-insensitively when it doesn't exist as is. `None` unless it leads to a file.
+fn join_case_insensitive(directory: &Path, components: &[&str]) -> Option<PathBuf> {
+
+	let mut path = directory.to_path_buf();
+	
+	for component in components {
+		let exact = path.join(component);
+		
+		if exact.exists() || *component == ".." {
+			path = exact;
+			continue;
+		}
+		
+		let lowercase = component.to_lowercase();
+		
+		path = fs::read_dir(&path).ok()?
+			.filter_map(Result::ok)
+			.map(|entry| entry.path())
+			.find(|entry| entry.file_name().is_some_and(|name| name.to_string_lossy().to_lowercase() == lowercase))?;
+	}
+	
+	if path.is_file() { Some(path) } else { None }
+	
+}
+
+/// This function find the texture with the given path, as written in the MTL file or the map library.
+/// Windows separators (`\`) are supported, and the path components are matched case-insensitively.
+/// A relative path is first searched in each directory, then its file name alone;
+/// an absolute path is used when it exists, its file name being searched in the directories otherwise.
+///
+/// # Examples
+///
+/// ```
+/// let directories = search_directories("models/house.obj", "house.mtl", &[]);
+///
+/// // models/Textures/Wood.PNG
+/// assert!(find_texture(&directories, "textures\\wood.png").is_some());
+/// // models/Stone.JPG, the directories being ignored
+/// assert!(find_texture(&directories, "C:\\Users\\artist\\stone.jpg").is_some());
+/// ```
+pub fn find_texture(directories: &[PathBuf], texture: &str) -> Option<PathBuf> {
+
+	let texture = texture.replace("\\", "/");
+	let components: Vec<&str> = texture.split('/').filter(|component| !component.is_empty() && *component != ".").collect();
+	let file_name = *components.last()?;
+	let absolute = texture.starts_with('/') || texture.chars().nth(1) == Some(':');
+	
+	if absolute && Path::new(&texture).is_file() {
+		return Some(PathBuf::from(texture));
+	}
+	
+	if !absolute {
+		if let Some(path) = directories.iter().find_map(|directory| join_case_insensitive(directory, &components)) {
+			return Some(path);
+		}
+	}
+	
+	directories.iter().find_map(|directory| join_case_insensitive(directory, &[file_name]))
+	
+}
+
+/// This function return the given file name, suffixed with a number when it is already `taken`
+/// (whatever the case, for the case-insensitive file systems and servers), so the textures
+/// written into a same directory (copies, resized textures and atlases) don't overwrite each other.
+///
+/// # Examples
+///
+/// ```
+/// let taken = vec!["wood.png".to_string(), "Wood_2.png".to_string()];
+///
+/// assert!(unique_file_name("WOOD.png", &taken) == "WOOD_3.png");
+/// assert!(unique_file_name("stone.jpg", &taken) == "stone.jpg");
+/// ```
+pub fn unique_file_name(file_name: &str, taken: &[String]) -> String {
+
+	let path = Path::new(file_name);
+	let stem = path.file_stem().map_or(String::new(), |stem| stem.to_string_lossy().into_owned());
+	let extension = path.extension().map_or(String::new(), |extension| format!(".{}", extension.to_string_lossy()));
+	
+	let is_taken = |name: &str| taken.iter().any(|other| other.to_lowercase() == name.to_lowercase());
+	let mut name = file_name.to_string();
+	let mut index = 2;
+	
+	while is_taken(&name) {
+		name = format!("{}_{}{}", stem, index, extension);
+		index += 1;
+	}
+	
+	name
+	
+}
+
+/// This function return the absolute form of the path, its existing part being canonicalized
+/// (the files written by the converter may not exist yet).
+fn absolute_path(path: &Path) -> PathBuf {
+
+	if let Ok(path) = path.canonicalize() {
+		return path;
+	}
+	
+	match (path.parent(), path.file_name()) {
+		(Some(parent), Some(name)) if parent != Path::new("") => absolute_path(parent).join(name),
+		_ => env::current_dir().map(|directory| directory.join(path)).unwrap_or_else(|_| path.to_path_buf())
+	}
+	
+}
+
+/// This function return the path of the file relatively to the `base` directory, with `/` separators
+/// (the absolute path when they don't share a root, like two Windows drives).
+///
+/// # Examples
+///
+/// ```
+/// assert!(relative_path(Path::new("/models/out"), Path::new("/models/textures/wood.png")) == "../textures/wood.png");
+/// ```
+pub fn relative_path(base: &Path, path: &Path) -> String {
+
+	let base = absolute_path(base);
+	let path = absolute_path(path);
+	let base: Vec<Component> = base.components().collect();
+	let target: Vec<Component> = path.components().collect();
+	let common = base.iter().zip(&target).take_while(|&(a, b)| a == b).count();
+	
+	if common == 0 {
+		return path.to_string_lossy().replace('\\', "/");
+	}
+	
+	let mut parts = vec!["..".to_string(); base.len() - common];
+	
+	parts.extend(target[common..].iter().map(|component| component.as_os_str().to_string_lossy().into_owned()));
+	parts.join("/")
+	
+}
+
+/// This function find the textures of the materials (see [find_texture](./fn.find_texture.html)) and reference
+/// the ones found by their path relatively to the `base` directory (the one of the output file),
+/// with the case of the files on the disk. The textures which can't be found are kept as written,
+/// with `/` separators.
+/// Return the files of the textures found by their new reference, and the textures which can't be found.
+pub fn resolve_textures(materials: &mut [Material], directories: &[PathBuf], base: &Path) -> (HashMap<String, PathBuf>, Vec<String>) {
+
+	let mut sources = HashMap::new();
+	let mut missing = Vec::new();
+	let mut renames = HashMap::new();
+	
+	for texture in texture_names(materials) {
+		let reference = match find_texture(directories, &texture) {
+			Some(path) => {
+				let reference = relative_path(base, &path);
+				sources.insert(reference.clone(), path);
+				reference
+			}
+			None => {
+				missing.push(texture.clone());
+				texture.replace('\\', "/")
+			}
+		};
+		
+		renames.insert(texture, reference);
+	}
+	
+	rename_textures(materials, &renames);
+	
+	(sources, missing)
+	
+}
+
+/// This function load the texture at the given path (see [find_texture](./fn.find_texture.html)),
+/// as an RGBA image. The error describes the file which couldn't be read.
+pub fn load_texture(path: &Path) -> Result<RgbaImage, String> {
+
+	match image::open(path) {
+		Ok(image) => Ok(image.to_rgba8()),
+		Err(error) => Err(format!("Couldn't read [{}] ({})", path.display(), error))
+	}
+	
+}
+
+/// This function sample the texture at the given texture coordinates with a bilinear filter,
+/// returning its (r, g, b, a) color with components between 0.0 and 1.0.
+/// The texture repeats (the exported textures use `RepeatWrapping`), and v goes upwards,
+/// the first row of the image being its top.
+///
+/// # Examples
+///
+/// ```
+/// let texture = RgbaImage::from_fn(2, 1, |x, _| if x == 0 { Rgba([0, 0, 0, 255]) } else { Rgba([255, 255, 255, 255]) });
+///
+/// assert!(sample_bilinear(&texture, 0.25, 0.5) == [0.0, 0.0, 0.0, 1.0]);
+/// assert!(sample_bilinear(&texture, 0.5, 0.5) == [0.5, 0.5, 0.5, 1.0]);
+/// ```
+pub fn sample_bilinear(texture: &RgbaImage, u: f64, v: f64) -> [f64; 4] {
+
+	let (width, height) = texture.dimensions();
+	
+	if width == 0 || height == 0 {
+		return [1.0; 4];
+	}
+	
+	// position in texels, the centers of the texels being at half coordinates
+	let x = u * width as f64 - 0.5;
+	let y = (1.0 - v) * height as f64 - 0.5;
+	let (left, top) = (x.floor(), y.floor());
+	let (dx, dy) = (x - left, y - top);
+	
+	let texel = |x: f64, y: f64| -> [f64; 4] {
+		let x = (x as i64).rem_euclid(width as i64) as u32;
+		let y = (y as i64).rem_euclid(height as i64) as u32;
+		let pixel = texture.get_pixel(x, y);
+		[
+			pixel[0] as f64 / 255.0,
+			pixel[1] as f64 / 255.0,
+			pixel[2] as f64 / 255.0,
+			pixel[3] as f64 / 255.0
+		]
+	};
+	
+	let samples = [
+		(texel(left, top), (1.0 - dx) * (1.0 - dy)),
+		(texel(left + 1.0, top), dx * (1.0 - dy)),
+		(texel(left, top + 1.0), (1.0 - dx) * dy),
+		(texel(left + 1.0, top + 1.0), dx * dy)
+	];
+	
+	let mut color = [0.0; 4];
+	
+	for &(texel, weight) in &samples {
+		for (component, value) in color.iter_mut().zip(&texel) {
+			*component += value * weight;
+		}
+	}
+	
+	color
+	
+}
+
+/// This function bake the diffuse textures of the materials into the vertex colors of the model
+/// (`--bake-texture-to-vertex-colors`): each vertex gets the color of the texture at its texture
+/// coordinates (see [sample_bilinear](./fn.sample_bilinear.html)), averaged over the corners of the faces
+/// sharing it and multiplied by its own color (the alpha channel of the textures is ignored).
+/// Vertices without a textured face keep their color, white without one.
+/// `textures` holds the decoded textures by name; the baked ones are removed from the materials.
+pub fn bake_texture_colors(model: &mut Model, materials: &mut [Material], textures: &HashMap<String, RgbaImage>) {
+
+	// sum of the sampled colors and number of samples of each vertex
+	let mut samples: Vec<([f64; 3], usize)> = vec![([0.0; 3], 0); model.vertices.len()];
+	
+	for face in &model.faces {
+	
+		let material = &materials[face.material.min(materials.len() - 1)];
+		
+		let texture = match material.map_diffuse.as_ref().and_then(|map| textures.get(map)) {
+			Some(texture) => texture,
+			None => continue
+		};
+		
+		if face.uv.len() < face.vertex.len() {
+			continue;
+		}
+		
+		for (&vertex, &uv) in face.vertex.iter().zip(&face.uv) {
+			if vertex < samples.len() && uv < model.uvs.len() {
+				let color = sample_bilinear(texture, model.uvs[uv].u, model.uvs[uv].v);
+				let sample = &mut samples[vertex];
+				
+				for (sum, value) in sample.0.iter_mut().zip(&color) {
+					*sum += value;
+				}
+				sample.1 += 1;
+			}
+		}
+	
+	}
+	
+	if samples.iter().any(|sample| sample.1 > 0) {
+		model.colors.resize(model.vertices.len(), [1.0; 4]);
+		
+		for (color, &(sum, count)) in model.colors.iter_mut().zip(&samples).filter(|&(_, sample)| sample.1 > 0) {
+			for (component, value) in color.iter_mut().zip(&sum) {
+				*component *= value / count as f64;
+			}
+		}
+	}
+	
+	for material in materials.iter_mut() {
+		if material.map_diffuse.as_ref().is_some_and(|map| textures.contains_key(map)) {
+			material.map_diffuse = None;
+		}
+	}
+	
+}
+
+/// The position of a texture packed into an atlas: the index of the atlas
+/// and the top left corner of the texture (without its padding), in texels.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct Placement {
+	pub atlas: 	usize,
+	pub x: 		u32,
+	pub y: 		u32
+}
+
+/// The atlases built from the diffuse textures of a model (see [build_atlases](./fn.build_atlases.html)),
+/// as (file name, image) pairs, and the textures left out of them with the reason.
+pub struct Atlases {
+	pub images: 	Vec<(String, RgbaImage)>,
+	pub skipped: 	Vec<(String, &'static str)>
+}
+
+/// Place the given rectangles (in the given order) on shelves in a width x height atlas,
+/// the rectangles which don't fit being `None`.
+fn pack_shelves(sizes: &[(u32, u32)], order: &[usize], width: u32, height: u32, padding: u32) -> Vec<Option<(u32, u32)>> {
+
+	let mut positions = vec![None; sizes.len()];
+	let (mut x, mut y, mut shelf) = (0, 0, 0);
+	
+	for &index in order {
+	
+		let (w, h) = (sizes[index].0 + 2 * padding, sizes[index].1 + 2 * padding);
+		
+		// on the current shelf, or on a new one below it
+		let (left, top) = if x + w > width { (0, y + shelf) } else { (x, y) };
+		
+		if left + w > width || top + h > height {
+			continue;
+		}
+		if top != y {
+			shelf = 0;
+		}
+		
+		x = left;
+		y = top;
+		positions[index] = Some((x + padding, y + padding));
+		x += w;
+		shelf = shelf.max(h);
+	
+	}
+	
+	positions
+	
+}
+
+/// This function pack rectangles of the given sizes, surrounded by `padding` texels, into
+/// power-of-two atlases of at most `max_size` x `max_size` texels: the rectangles are sorted by height
+/// and placed on shelves, in the smallest atlas holding all of them, or in a `max_size` atlas followed
+/// by other ones. Returns the size of the atlases and the placement of each rectangle,
+/// `None` when it is larger than an atlas.
+///
+/// # Examples
+///
+/// ```
+/// let (atlases, placements) = pack_atlases(&[(64, 64), (32, 64), (256, 8)], 128, 0);
+///
+/// assert!(atlases == vec![(128, 64)]);
+/// assert!(placements[0] == Some(Placement { atlas: 0, x: 0, y: 0 }));
+/// assert!(placements[1] == Some(Placement { atlas: 0, x: 64, y: 0 }));
+/// assert!(placements[2].is_none());
+/// ```
+pub fn pack_atlases(sizes: &[(u32, u32)], max_size: u32, padding: u32) -> (Vec<(u32, u32)>, Vec<Option<Placement>>) {
+
+	let mut atlases = Vec::new();
+	let mut placements = vec![None; sizes.len()];
+	
+	// the tallest rectangles first, then the widest
+	let mut remaining: Vec<usize> = (0..sizes.len())
+		.filter(|&index| sizes[index].0 + 2 * padding <= max_size && sizes[index].1 + 2 * padding <= max_size)
+		.collect();
+	
+	remaining.sort_by(|&a, &b| (sizes[b].1, sizes[b].0).cmp(&(sizes[a].1, sizes[a].0)));
+	
+	while !remaining.is_empty() {
+	
+		let largest = remaining.iter().map(|&index| sizes[index].0.max(sizes[index].1) + 2 * padding).max().unwrap_or(1);
+		let (mut width, mut height) = (largest.next_power_of_two(), largest.next_power_of_two());
+		
+		// grow the atlas until everything fits, or it reaches the maximum size
+		let positions = loop {
+			let positions = pack_shelves(sizes, &remaining, width, height, padding);
+			
+			if remaining.iter().all(|&index| positions[index].is_some()) || (width >= max_size && height >= max_size) {
+				break positions;
+			}
+			if width <= height {
+				width = (width * 2).min(max_size);
+			} else {
+				height = (height * 2).min(max_size);
+			}
+		};
+		
+		for &index in &remaining {
+			if let Some((x, y)) = positions[index] {
+				placements[index] = Some(Placement { atlas: atlases.len(), x, y });
+			}
+		}
+		
+		remaining.retain(|&index| positions[index].is_none());
+		atlases.push((width, height));
+	
+	}
+	
+	(atlases, placements)
+	
+}
+
+/// This function pack the diffuse textures of the materials into atlases of at most `max_size` texels
+/// (see [pack_atlases](./fn.pack_atlases.html)), named `name_0.png`, `name_1.png`..., and update the model to use them:
+/// the texture coordinates of the faces are moved into the atlases (duplicated when they are shared
+/// by several textures), and the materials only differing by their texture are merged.
+/// `textures` holds the decoded textures by name. A texture is left out when it repeats (texture coordinates
+/// outside [0, 1]), is larger than an atlas or is used with other textures sharing its coordinates
+/// (bump, alpha... maps); nothing is packed with less than two textures.
+pub fn build_atlases(model: &mut Model, materials: &mut Vec<Material>, textures: &HashMap<String, RgbaImage>, max_size: u32, name: &str) -> Atlases {
+
+	let mut skipped = Vec::new();
+	
+	// the textures which can be packed, and the material using each of them
+	let mut names: Vec<String> = Vec::new();
+	let mut packed: Vec<Option<usize>> = vec![None; materials.len()];
+	
+	for (index, material) in materials.iter().enumerate() {
+		let texture = match material.map_diffuse {
+			Some(ref texture) if textures.contains_key(texture) => texture,
+			_ => continue
+		};
+		
+		let others = [
+			&material.map_ambient, &material.map_specular, &material.map_bump, &material.map_alpha,
+			&material.map_normal, &material.map_roughness, &material.map_metalness
+		];
+		
+		if others.iter().any(|map| map.is_some()) {
+			skipped.push((texture.clone(), "its material has other textures"));
+			continue;
+		}
+		
+		packed[index] = match names.iter().position(|name| name == texture) {
+			Some(position) => Some(position),
+			None => {
+				names.push(texture.clone());
+				Some(names.len() - 1)
+			}
+		};
+	}
+	
+	let mut eligible = vec![true; names.len()];
+	
+	for face in &model.faces {
+		if let Some(Some(texture)) = packed.get(face.material) {
+			let outside = face.uv.iter()
+				.filter_map(|&uv| model.uvs.get(uv))
+				.any(|uv| uv.u < -UV_EPSILON || uv.u > 1.0 + UV_EPSILON || uv.v < -UV_EPSILON || uv.v > 1.0 + UV_EPSILON);
+			
+			if outside && eligible[*texture] {
+				eligible[*texture] = false;
+				skipped.push((names[*texture].clone(), "it repeats (texture coordinates outside [0, 1])"));
+			}
+		}
+	}
+	
+	let candidates: Vec<usize> = (0..names.len()).filter(|&texture| eligible[texture]).collect();
+	let sizes: Vec<(u32, u32)> = candidates.iter().map(|&texture| textures[&names[texture]].dimensions()).collect();
+	
+	let (atlas_sizes, packing) = pack_atlases(&sizes, max_size, ATLAS_PADDING);
+	
+	// placement of each texture
+	let mut placements: Vec<Option<Placement>> = vec![None; names.len()];
+	
+	for (&texture, &placement) in candidates.iter().zip(&packing) {
+		if placement.is_none() {
+			skipped.push((names[texture].clone(), "it is larger than an atlas"));
+		}
+		placements[texture] = placement;
+	}
+	
+	if placements.iter().filter(|placement| placement.is_some()).count() < 2 {
+		return Atlases { images: Vec::new(), skipped };
+	}
+	
+	let mut images: Vec<(String, RgbaImage)> = atlas_sizes.iter().enumerate()
+		.map(|(index, &(width, height))| (format!("{}_{}.png", name, index), RgbaImage::new(width, height)))
+		.collect();
+	
+	// the textures are copied with their border texels repeated in the padding
+	for (texture, placement) in names.iter().zip(&placements) {
+		if let Some(placement) = *placement {
+			let source = &textures[texture];
+			let (width, height) = source.dimensions();
+			let atlas = &mut images[placement.atlas].1;
+			let padding = ATLAS_PADDING as i64;
+			
+			for y in -padding..height as i64 + padding {
+				for x in -padding..width as i64 + padding {
+					let pixel = *source.get_pixel(x.clamp(0, width as i64 - 1) as u32, y.clamp(0, height as i64 - 1) as u32);
+					atlas.put_pixel((placement.x as i64 + x) as u32, (placement.y as i64 + y) as u32, pixel);
+				}
+			}
+		}
+	}
+	
+	// moves texture coordinates into the atlas of the given texture
+	let transform = |uv: &Uv, texture: Option<usize>| -> Uv {
+		match texture.and_then(|texture| placements[texture].map(|placement| (placement, textures[&names[texture]].dimensions()))) {
+			Some((placement, (width, height))) => {
+				let (atlas_width, atlas_height) = atlas_sizes[placement.atlas];
+				Uv {
+					u: (placement.x as f64 + uv.u * width as f64) / atlas_width as f64,
+					v: 1.0 - (placement.y as f64 + (1.0 - uv.v) * height as f64) / atlas_height as f64,
+					w: uv.w
+				}
+			}
+			None => *uv
+		}
+	};
+	
+	let original = model.uvs.clone();
+	let mut owners: Vec<Option<Option<usize>>> = vec![None; original.len()];
+	let mut copies: HashMap<(usize, Option<usize>), usize> = HashMap::new();
+	
+	for face in &mut model.faces {
+	
+		let texture = packed.get(face.material).cloned().unwrap_or(None).filter(|&texture| placements[texture].is_some());
+		
+		for uv in face.uv.iter_mut().filter(|uv| **uv < original.len()) {
+			match owners[*uv] {
+				None => {
+					owners[*uv] = Some(texture);
+					model.uvs[*uv] = transform(&original[*uv], texture);
+				}
+				Some(owner) if owner == texture => (),
+				Some(_) => {
+					*uv = match copies.get(&(*uv, texture)) {
+						Some(&copy) => copy,
+						None => {
+							model.uvs.push(transform(&original[*uv], texture));
+							copies.insert((*uv, texture), model.uvs.len() - 1);
+							model.uvs.len() - 1
+						}
+					};
+				}
+			}
+		}
+	
+	}
+	
+	// the packed materials use their atlas, and the identical ones are merged
+	let mut merged: Vec<Material> = Vec::new();
+	let mut atlased: Vec<bool> = Vec::new();
+	let mut indices: Vec<usize> = Vec::new();
+	
+	for (material, texture) in materials.iter().zip(&packed) {
+		match texture.and_then(|texture| placements[texture]) {
+			Some(placement) => {
+				let material = Material { map_diffuse: Some(images[placement.atlas].0.clone()), ..material.clone() };
+				let same = |other: &Material| Material { name: String::new(), ..other.clone() } == Material { name: String::new(), ..material.clone() };
+				
+				match (0..merged.len()).find(|&index| atlased[index] && same(&merged[index])) {
+					Some(index) => indices.push(index),
+					None => {
+						merged.push(material);
+						atlased.push(true);
+						indices.push(merged.len() - 1);
+					}
+				}
+			}
+			None => {
+				merged.push(material.clone());
+				atlased.push(false);
+				indices.push(merged.len() - 1);
+			}
+		}
+	}
+	
+	for element in model.faces.iter_mut().chain(model.lines.iter_mut()).chain(model.points.iter_mut()) {
+		element.material = indices[element.material.min(indices.len() - 1)];
+	}
+	
+	model.materials = merged.iter().map(|material| material.name.clone()).collect();
+	model.maps = vec![None; merged.len()];
+	*materials = merged;
+	
+	Atlases { images, skipped }
+	
+}
+
+/// This function list the textures used by the materials (all their maps), each one once.
+pub fn texture_names(materials: &[Material]) -> Vec<String> {
+
+	let mut names: Vec<String> = Vec::new();
+	
+	for material in materials {
+		let maps = [
+			&material.map_ambient, &material.map_diffuse, &material.map_specular, &material.map_bump,
+			&material.map_alpha, &material.map_normal, &material.map_roughness, &material.map_metalness
+		];
+		
+		for map in maps.iter().filter_map(|map| map.as_ref()) {
+			if !names.contains(map) {
+				names.push(map.clone());
+			}
+		}
+	}
+	
+	names
+	
+}
+
+/// This function replace the textures of all the maps of the materials, following the given renames
+/// (the textures which aren't renamed are kept).
+pub fn rename_textures(materials: &mut [Material], renames: &HashMap<String, String>) {
+
+	for material in materials {
+		let maps = [
+			&mut material.map_ambient, &mut material.map_diffuse, &mut material.map_specular, &mut material.map_bump,
+			&mut material.map_alpha, &mut material.map_normal, &mut material.map_roughness, &mut material.map_metalness
+		];
+		
+		for map in maps {
+			if let Some(to) = map.as_ref().and_then(|map| renames.get(map)) {
+				*map = Some(to.clone());
+			}
+		}
+	}
+	
+}
+
+/// This function return the power of two nearest to the given size (the smaller one in case of a tie).
+///
+/// # Examples
+///
+/// ```
+/// assert!(nearest_power_of_two(300) == 256);
+/// assert!(nearest_power_of_two(400) == 512);
+/// assert!(nearest_power_of_two(384) == 256);
+/// ```
+pub fn nearest_power_of_two(size: u32) -> u32 {
+
+	let size = size.max(1);
+	let upper = size.next_power_of_two();
+	let lower = if upper > size { upper / 2 } else { upper };
+	
+	if size - lower <= upper - size { lower } else { upper }
+	
+}
+
+/// This function return the size a texture of the given size should be resized to for WebGL:
+/// each dimension is rounded to the nearest power of two, and halved until it is at most `max_size`.
+/// `None` when the texture already has a power of two size of at most `max_size`.
+///
+/// # Examples
+///
+/// ```
+/// assert!(power_of_two_size(300, 1000, 4096) == Some((256, 1024)));
+/// assert!(power_of_two_size(8192, 512, 2048) == Some((2048, 512)));
+/// assert!(power_of_two_size(512, 512, 2048).is_none());
+/// ```
+pub fn power_of_two_size(width: u32, height: u32, max_size: u32) -> Option<(u32, u32)> {
+
+	let resize = |size: u32| {
+		let mut size = nearest_power_of_two(size);
+		while size > max_size.max(1) {
+			size /= 2;
+		}
+		size
+	};
+	
+	if (resize(width), resize(height)) == (width, height) {
+		None
+	} else {
+		Some((resize(width), resize(height)))
+	}
+	
+}
+
+/// This function resample the texture at `source` to the given size (with a Catmull-Rom filter)
+/// and write it to `target`, in the format of its extension.
+/// The error describes the file which couldn't be read or written.
+pub fn resize_texture(source: &Path, target: &Path, width: u32, height: u32) -> Result<(), String> {
+
+	let image = match image::open(source) {
+		Ok(image) => image,
+		Err(error) => return Err(format!("Couldn't read [{}] ({})", source.display(), error))
+	};
+	
+	match image.resize_exact(width, height, FilterType::CatmullRom).save(target) {
+		Ok(()) => Ok(()),
+		Err(error) => Err(format!("Couldn't write [{}] ({})", target.display(), error))
+	}
+	
+}
+
+/// The test module of the textures
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+	use converter::read_obj;
+	
+	#[test]
+	fn test_sample_bilinear() {
+	
+		// red and green on the top row, blue and white on the bottom one
+		let texture = RgbaImage::from_raw(2, 2, vec![
+			255, 0, 0, 255, 	0, 255, 0, 255,
+			0, 0, 255, 255, 	255, 255, 255, 0
+		]).unwrap();
+		
+		// texel centers
+		assert!(sample_bilinear(&texture, 0.25, 0.75) == [1.0, 0.0, 0.0, 1.0]);
+		assert!(sample_bilinear(&texture, 0.75, 0.25) == [1.0, 1.0, 1.0, 0.0]);
+		
+		// between the texels
+		assert!(sample_bilinear(&texture, 0.5, 0.75) == [0.5, 0.5, 0.0, 1.0]);
+		assert!(sample_bilinear(&texture, 0.5, 0.5) == [0.5, 0.5, 0.5, 0.75]);
+		
+		// the texture repeats
+		assert!(sample_bilinear(&texture, 1.25, -0.75) == [0.0, 0.0, 1.0, 1.0]);
+		assert!(sample_bilinear(&texture, 0.0, 0.75) == [0.5, 0.5, 0.0, 1.0]);
+	
+	}
+	
+	#[test]
+	fn test_bake_texture_colors() {
+	
+		let mut model = read_obj("\
+			v 0 0 0\n\
+			v 1 0 0\n\
+			v 1 1 0\n\
+			v 0 1 0\n\
+			v 2 0 0\n\
+			vt 0.25 0.25\n\
+			vt 0.75 0.25\n\
+			vt 0.75 0.75\n\
+			vt 0.25 0.75\n\
+			usemtl wood\n\
+			f 1/1 2/2 3/3\n\
+			f 1/1 3/2 4/4\n\
+			usemtl plain\n\
+			f 2 5 3\n\
+		".as_bytes());
+		
+		let mut materials = vec![
+			Material { name: "wood".to_string(), map_diffuse: Some("wood.png".to_string()), ..Material::default() },
+			Material { name: "plain".to_string(), map_diffuse: Some("missing.png".to_string()), ..Material::default() }
+		];
+		
+		let texture = RgbaImage::from_raw(2, 2, vec![
+			255, 0, 0, 255, 	0, 255, 0, 255,
+			0, 0, 255, 255, 	255, 255, 255, 255
+		]).unwrap();
+		
+		let mut textures = HashMap::new();
+		
+		textures.insert("wood.png".to_string(), texture);
+		
+		bake_texture_colors(&mut model, &mut materials, &textures);
+		
+		// the third vertex is averaged over its two corners, white and green
+		assert!(model.colors == vec![
+			[0.0, 0.0, 1.0, 1.0],
+			[1.0, 1.0, 1.0, 1.0],
+			[0.5, 1.0, 0.5, 1.0],
+			[1.0, 0.0, 0.0, 1.0],
+			[1.0, 1.0, 1.0, 1.0]
+		]);
+		assert!(materials[0].map_diffuse.is_none());
+		assert!(materials[1].map_diffuse == Some("missing.png".to_string()));
+	
+	}
+	
+	#[test]
+	fn test_pack_atlases() {
+	
+		let (atlases, placements) = pack_atlases(&[(64, 64), (32, 64), (256, 8)], 128, 0);
+		
+		assert!(atlases == vec![(128, 64)]);
+		assert!(placements == vec![
+			Some(Placement { atlas: 0, x: 0, y: 0 }),
+			Some(Placement { atlas: 0, x: 64, y: 0 }),
+			None
+		]);
+		
+		// padding around each texture, the tallest ones first
+		let (atlases, placements) = pack_atlases(&[(8, 8), (16, 16), (8, 8)], 64, 2);
+		
+		assert!(atlases == vec![(32, 32)]);
+		assert!(placements == vec![
+			Some(Placement { atlas: 0, x: 22, y: 2 }),
+			Some(Placement { atlas: 0, x: 2, y: 2 }),
+			Some(Placement { atlas: 0, x: 2, y: 22 })
+		]);
+		
+		// the textures which don't fit in an atlas go into the next ones
+		let (atlases, placements) = pack_atlases(&[(100, 100), (100, 100), (20, 20)], 128, 0);
+		
+		assert!(atlases == vec![(128, 128), (128, 128)]);
+		assert!(placements[0] == Some(Placement { atlas: 0, x: 0, y: 0 }));
+		assert!(placements[1] == Some(Placement { atlas: 1, x: 0, y: 0 }));
+		assert!(placements[2] == Some(Placement { atlas: 0, x: 100, y: 0 }));
+	
+	}
+	
+	#[test]
+	fn test_build_atlases() {
+	
+		let mut model = read_obj("\
+			v 0 0 0\n\
+			v 1 0 0\n\
+			v 1 1 0\n\
+			vt 0 0\n\
+			vt 1 0\n\
+			vt 1 1\n\
+			vt 2 2\n\
+			usemtl red\n\
+			f 1/1 2/2 3/3\n\
+			usemtl green\n\
+			f 1/1 2/2 3/3\n\
+			usemtl tiled\n\
+			f 1/1 2/2 3/4\n\
+			usemtl bumpy\n\
+			f 1/1 2/2 3/3\n\
+		".as_bytes());
+		
+		let mut materials = vec![
+			Material { name: "red".to_string(), map_diffuse: Some("red.png".to_string()), ..Material::default() },
+			Material { name: "green".to_string(), map_diffuse: Some("green.png".to_string()), ..Material::default() },
+			Material { name: "tiled".to_string(), map_diffuse: Some("tiled.png".to_string()), ..Material::default() },
+			Material {
+				name: "bumpy".to_string(),
+				map_diffuse: Some("red.png".to_string()),
+				map_bump: Some("bump.png".to_string()),
+				..Material::default()
+			}
+		];
+		
+		let mut textures = HashMap::new();
+		
+		textures.insert("red.png".to_string(), RgbaImage::from_pixel(8, 8, image::Rgba([255, 0, 0, 255])));
+		textures.insert("green.png".to_string(), RgbaImage::from_pixel(4, 8, image::Rgba([0, 255, 0, 255])));
+		textures.insert("tiled.png".to_string(), RgbaImage::from_pixel(8, 8, image::Rgba([0, 0, 255, 255])));
+		
+		let atlases = build_atlases(&mut model, &mut materials, &textures, 64, "model_atlas");
+		
+		assert!(atlases.skipped == vec![
+			("red.png".to_string(), "its material has other textures"),
+			("tiled.png".to_string(), "it repeats (texture coordinates outside [0, 1])")
+		]);
+		
+		// 8 + 4 texels wide with the padding, the red texture first
+		assert!(atlases.images.len() == 1);
+		assert!(atlases.images[0].0 == "model_atlas_0.png");
+		assert!(atlases.images[0].1.dimensions() == (32, 16));
+		assert!(*atlases.images[0].1.get_pixel(0, 0) == image::Rgba([255, 0, 0, 255]));
+		assert!(*atlases.images[0].1.get_pixel(15, 15) == image::Rgba([255, 0, 0, 255]));
+		assert!(*atlases.images[0].1.get_pixel(17, 0) == image::Rgba([0, 255, 0, 255]));
+		
+		// the red and green materials are merged
+		assert!(materials.len() == 3);
+		assert!(materials[0].name == "red");
+		assert!(materials[0].map_diffuse == Some("model_atlas_0.png".to_string()));
+		assert!(materials[1].map_diffuse == Some("tiled.png".to_string()));
+		assert!(materials[2].map_diffuse == Some("red.png".to_string()));
+		assert!(model.materials == vec!["red".to_string(), "tiled".to_string(), "bumpy".to_string()]);
+		
+		let indices: Vec<usize> = model.faces.iter().map(|face| face.material).collect();
+		
+		assert!(indices == vec![0, 0, 1, 2]);
+		
+		// the texture coordinates shared by several textures are duplicated
+		assert!(model.faces[0].uv == vec![0, 1, 2]);
+		assert!(model.faces[1].uv == vec![4, 5, 6]);
+		assert!(model.faces[2].uv == vec![7, 8, 3]);
+		assert!(model.faces[3].uv == vec![7, 8, 9]);
+		
+		let uv = |index: usize| (model.uvs[index].u, model.uvs[index].v);
+		
+		assert!(uv(0) == (4.0 / 32.0, 1.0 - 12.0 / 16.0));
+		assert!(uv(2) == (12.0 / 32.0, 1.0 - 4.0 / 16.0));
+		assert!(uv(4) == (20.0 / 32.0, 1.0 - 12.0 / 16.0));
+		assert!(uv(6) == (24.0 / 32.0, 1.0 - 4.0 / 16.0));
+		assert!(uv(7) == (0.0, 0.0));
+		assert!(uv(9) == (1.0, 1.0));
+	
+	}
+	
+	#[test]
+	fn test_texture_names() {
+	
+		let mut materials = vec![
+			Material { map_diffuse: Some("wood.png".to_string()), map_bump: Some("bump.png".to_string()), ..Material::default() },
+			Material { map_ambient: Some("wood.png".to_string()), map_normal: Some("normal.png".to_string()), ..Material::default() },
+			Material::default()
+		];
+		
+		assert!(texture_names(&materials) == vec!["wood.png".to_string(), "bump.png".to_string(), "normal.png".to_string()]);
+		
+		let mut renames = HashMap::new();
+		
+		renames.insert("wood.png".to_string(), "bump.png".to_string());
+		renames.insert("bump.png".to_string(), "wood.png".to_string());
+		
+		rename_textures(&mut materials, &renames);
+		
+		assert!(materials[0].map_diffuse == Some("bump.png".to_string()));
+		assert!(materials[1].map_ambient == Some("bump.png".to_string()));
+		assert!(materials[0].map_bump == Some("wood.png".to_string()));
+		assert!(materials[1].map_normal == Some("normal.png".to_string()));
+	
+	}
+	
+	#[test]
+	fn test_power_of_two_size() {
+	
+		assert!(nearest_power_of_two(0) == 1);
+		assert!(nearest_power_of_two(1) == 1);
+		assert!(nearest_power_of_two(3) == 2);
+		assert!(nearest_power_of_two(1024) == 1024);
+		assert!(nearest_power_of_two(1500) == 1024);
+		assert!(nearest_power_of_two(1600) == 2048);
+		
+		assert!(power_of_two_size(1024, 256, 4096).is_none());
+		assert!(power_of_two_size(1000, 256, 4096) == Some((1024, 256)));
+		assert!(power_of_two_size(4096, 4096, 1024) == Some((1024, 1024)));
+		assert!(power_of_two_size(100, 100, 100) == Some((64, 64)));
+	
+	}
+	
+	#[test]
+	fn test_find_texture() {
+	
+		let directory = ::std::env::temp_dir().join(format!("obj2three-textures-{}", ::std::process::id()));
+		
+		fs::create_dir_all(directory.join("models/Materials/Maps")).unwrap();
+		fs::create_dir_all(directory.join("shared")).unwrap();
+		fs::write(directory.join("models/Materials/Maps/Wood.PNG"), "").unwrap();
+		fs::write(directory.join("models/stone.jpg"), "").unwrap();
+		fs::write(directory.join("shared/metal.png"), "").unwrap();
+		
+		let obj = directory.join("models/house.obj");
+		let shared = directory.join("shared").to_string_lossy().into_owned();
+		let directories = search_directories(&obj.to_string_lossy(), "Materials/house.mtl", &[shared]);
+		
+		assert!(directories.len() == 3);
+		
+		let wood = Some(directory.join("models/Materials/Maps/Wood.PNG"));
+		
+		assert!(find_texture(&directories, "maps\\wood.png") == wood);
+		assert!(find_texture(&directories, "./MAPS/wood.png") == wood);
+		assert!(find_texture(&directories, "C:\\Users\\artist\\wood.png").is_none());
+		assert!(find_texture(&directories, "C:\\Users\\artist\\STONE.jpg") == Some(directory.join("models/stone.jpg")));
+		assert!(find_texture(&directories, "textures/Stone.jpg") == Some(directory.join("models/stone.jpg")));
+		assert!(find_texture(&directories, "metal.png") == Some(directory.join("shared/metal.png")));
+		assert!(find_texture(&directories, "missing.png").is_none());
+		assert!(find_texture(&directories, "").is_none());
+		assert!(load_texture(&directory.join("shared/metal.png")).is_err());
+		
+		fs::remove_dir_all(&directory).unwrap();
+	
+	}
+	
+	#[test]
+	fn test_unique_file_name() {
+	
+		let taken = vec!["wood.png".to_string(), "WOOD_2.png".to_string()];
+		
+		assert!(unique_file_name("Wood.png", &taken) == "Wood_3.png");
+		assert!(unique_file_name("stone", &taken) == "stone");
+		assert!(unique_file_name("wood.png", &[]) == "wood.png");
+	
+	}
+	
+	#[test]
+	fn test_resolve_textures() {
+	
+		let directory = ::std::env::temp_dir().join(format!("obj2three-references-{}", ::std::process::id()));
+		
+		fs::create_dir_all(directory.join("models/maps")).unwrap();
+		fs::create_dir_all(directory.join("models/old")).unwrap();
+		fs::create_dir_all(directory.join("out")).unwrap();
+		fs::write(directory.join("models/maps/wood.png"), "").unwrap();
+		fs::write(directory.join("models/old/Wood.PNG"), "").unwrap();
+		
+		let mut materials = vec![
+			Material { map_diffuse: Some("maps\\wood.png".to_string()), map_bump: Some("old/WOOD.png".to_string()), ..Material::default() },
+			Material { map_diffuse: Some("textures\\missing.png".to_string()), ..Material::default() }
+		];
+		let directories = search_directories(&directory.join("models/house.obj").to_string_lossy(), "house.mtl", &[]);
+		
+		// without --copy-textures, the output references the files where they are
+		let (sources, missing) = resolve_textures(&mut materials, &directories, &directory.join("out"));
+		
+		assert!(materials[0].map_diffuse == Some("../models/maps/wood.png".to_string()));
+		assert!(materials[0].map_bump == Some("../models/old/Wood.PNG".to_string()));
+		assert!(materials[1].map_diffuse == Some("textures/missing.png".to_string()));
+		assert!(sources.len() == 2);
+		assert!(sources["../models/old/Wood.PNG"] == directory.join("models/old/Wood.PNG"));
+		assert!(missing == vec!["textures\\missing.png".to_string()]);
+		
+		assert!(relative_path(&directory.join("models"), &directory.join("models/maps/wood.png")) == "maps/wood.png");
+		assert!(relative_path(&directory.join("models/maps"), &directory.join("models/new/wood.png")) == "../new/wood.png");
+		
+		fs::remove_dir_all(&directory).unwrap();
+	
+	}
+}